@@ -1,60 +1,8 @@
 use crate::day::Day;
+use crate::grid::{Coord, Grid};
 
-use std::collections::HashSet;
-
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-struct Coord {
-    x: usize,
-    y: usize,
-}
-
-impl Coord {
-    fn neighbors(&self) -> impl Iterator<Item = Coord> {
-        CoordNeighborIterator::new(*self)
-    }
-}
-
-struct CoordNeighborIterator {
-    coord: Coord,
-    idx: usize,
-}
-
-impl CoordNeighborIterator {
-    const DIRS: [(i32, i32); 8] = [
-        (-1, -1), // NW
-        (0, -1),  // N
-        (1, -1),  // NE
-        (-1, 0),  // W
-        (1, 0),   // E
-        (-1, 1),  // SW
-        (0, 1),   // S
-        (1, 1),   // SE
-    ];
-
-    fn new(coord: Coord) -> Self {
-        CoordNeighborIterator { coord, idx: 0 }
-    }
-}
-
-impl Iterator for CoordNeighborIterator {
-    type Item = Coord;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        while self.idx < Self::DIRS.len() {
-            let (dx, dy) = Self::DIRS[self.idx];
-            self.idx += 1;
-
-            let x = usize::try_from(self.coord.x as i32 + dx).ok();
-            let y = usize::try_from(self.coord.y as i32 + dy).ok();
-
-            if let (Some(x), Some(y)) = (x, y) {
-                return Some(Coord { x, y });
-            }
-        }
-
-        None
-    }
-}
+use std::collections::BTreeMap;
+use std::iter;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 struct Part {
@@ -73,12 +21,22 @@ impl Part {
             is_neighbor: false,
         }
     }
+}
+
+/// What makes a symbol a gear: its character and how many distinct parts it
+/// has to touch.
+#[derive(Debug, Clone, Copy)]
+struct GearRule {
+    symbol: char,
+    part_count: usize,
+}
 
-    fn contains(&self, coord: Coord) -> bool {
-        coord.x >= self.start.x
-            && coord.x <= self.end.x
-            && coord.y >= self.start.y
-            && coord.y <= self.end.y
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbol: '*',
+            part_count: 2,
+        }
     }
 }
 
@@ -88,93 +46,297 @@ struct Symbol {
     coord: Coord,
 }
 
+/// How a cell of the schematic is drawn by [`Schematic::render`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellKind {
+    Empty,
+    CountedPart,
+    IsolatedPart,
+    Symbol,
+    Gear,
+}
+
+impl CellKind {
+    const RESET: &'static str = "\x1b[0m";
+
+    fn color(&self) -> Option<&'static str> {
+        match self {
+            CellKind::Empty => None,
+            CellKind::CountedPart => Some("\x1b[32m"), // green
+            CellKind::IsolatedPart => Some("\x1b[31m"), // red
+            CellKind::Symbol => Some("\x1b[36m"),      // cyan
+            CellKind::Gear => Some("\x1b[1;33m"),      // bold yellow
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Schematic {
+    grid: Grid<char>,
     parts: Vec<Part>,
     symbols: Vec<Symbol>,
+    /// Index into `parts` for every cell covered by a part number.
+    part_index: Grid<Option<usize>>,
 }
 
 impl Schematic {
-    fn new(parts: Vec<Part>, symbols: Vec<Symbol>) -> Self {
-        Schematic { parts, symbols }
+    fn new(grid: Grid<char>, parts: Vec<Part>, symbols: Vec<Symbol>) -> Self {
+        let mut part_index = grid.map(|_| None);
+        for (idx, part) in parts.iter().enumerate() {
+            for x in part.start.x..=part.end.x {
+                part_index[Coord { x, y: part.start.y }] = Some(idx);
+            }
+        }
+
+        Schematic {
+            grid,
+            parts,
+            symbols,
+            part_index,
+        }
+    }
+
+    /// Indices of the distinct parts touching `coord`. A cell has at most eight
+    /// neighbors, so the dedup scan is constant time.
+    fn adjacent_parts(&self, coord: Coord) -> Vec<usize> {
+        let mut adjacent = Vec::new();
+        self.grid
+            .neighbors8(coord)
+            .filter_map(|neighbor| self.part_index[neighbor])
+            .for_each(|idx| {
+                if !adjacent.contains(&idx) {
+                    adjacent.push(idx);
+                }
+            });
+
+        adjacent
     }
 
-    // N^2 let's fucking go
     fn mark_neighbor_parts(&mut self) {
-        self.symbols.iter().for_each(|symbol| {
-            symbol.coord.neighbors().for_each(|neighbor| {
-                self.parts.iter_mut().for_each(|part| {
-                    if part.contains(neighbor) {
-                        part.is_neighbor = true;
-                    }
-                })
-            })
-        })
+        let adjacent = self
+            .symbols
+            .iter()
+            .flat_map(|symbol| self.adjacent_parts(symbol.coord))
+            .collect::<Vec<_>>();
+
+        for idx in adjacent {
+            self.parts[idx].is_neighbor = true;
+        }
     }
 
-    fn calc_gear_ratio(&mut self) -> usize {
-        let Schematic { parts, symbols } = self;
-        symbols
+    fn is_gear(&self, symbol: &Symbol, rule: GearRule) -> bool {
+        symbol.symbol == rule.symbol && self.adjacent_parts(symbol.coord).len() == rule.part_count
+    }
+
+    fn calc_gear_ratio(&self, rule: GearRule) -> usize {
+        self.symbols
             .iter()
-            .filter(|symbol| symbol.symbol.eq_ignore_ascii_case(&'*'))
+            .filter(|symbol| self.is_gear(symbol, rule))
             .map(|symbol| {
-                symbol
-                    .coord
-                    .neighbors()
-                    .flat_map(|neighbor| {
-                        parts
-                            .iter()
-                            .filter(|part| part.contains(neighbor))
-                            .collect::<Vec<_>>()
-                    })
-                    .collect::<HashSet<_>>()
+                self.adjacent_parts(symbol.coord)
+                    .iter()
+                    .map(|idx| self.parts[*idx].number)
+                    .product::<usize>()
             })
-            .filter(|parts| parts.len() == 2)
-            .map(|parts| parts.iter().map(|part| part.number).product::<usize>())
             .sum()
     }
+
+    /// Distinct parts touching at least one symbol of each kind.
+    fn parts_by_symbol_kind(&self) -> BTreeMap<char, Vec<usize>> {
+        let mut by_kind = BTreeMap::<char, Vec<usize>>::new();
+
+        for symbol in &self.symbols {
+            let parts = by_kind.entry(symbol.symbol).or_default();
+            for idx in self.adjacent_parts(symbol.coord) {
+                if !parts.contains(&idx) {
+                    parts.push(idx);
+                }
+            }
+        }
+
+        by_kind
+    }
+
+    /// Symbols touching exactly `count` distinct parts.
+    fn symbols_with_part_count(&self, count: usize) -> Vec<&Symbol> {
+        self.symbols
+            .iter()
+            .filter(|symbol| self.adjacent_parts(symbol.coord).len() == count)
+            .collect()
+    }
+
+    /// Parts touching more than one symbol, with the symbols they touch.
+    fn shared_parts(&self) -> Vec<(usize, Vec<&Symbol>)> {
+        let mut touching = vec![Vec::new(); self.parts.len()];
+        for symbol in &self.symbols {
+            for idx in self.adjacent_parts(symbol.coord) {
+                touching[idx].push(symbol);
+            }
+        }
+
+        touching
+            .into_iter()
+            .enumerate()
+            .filter(|(_, symbols)| symbols.len() > 1)
+            .collect()
+    }
+
+    fn stats(&self, rule: GearRule) -> String {
+        let mut out = String::from("Symbol  Count  Parts  Part sum\n");
+        for (kind, parts) in self.parts_by_symbol_kind() {
+            let count = self.symbols.iter().filter(|s| s.symbol == kind).count();
+            let sum = parts
+                .iter()
+                .map(|idx| self.parts[*idx].number)
+                .sum::<usize>();
+            out.push_str(&format!(
+                "{:<6}  {:>5}  {:>5}  {:>8}\n",
+                kind,
+                count,
+                parts.len(),
+                sum
+            ));
+        }
+
+        out.push_str("\nSymbols by adjacent part count:\n");
+        let max_count = self
+            .symbols
+            .iter()
+            .map(|symbol| self.adjacent_parts(symbol.coord).len())
+            .max()
+            .unwrap_or(0);
+        for count in 0..=max_count {
+            let symbols = self.symbols_with_part_count(count);
+            if !symbols.is_empty() {
+                out.push_str(&format!("  {}: {}\n", count, symbols.len()));
+            }
+        }
+
+        out.push_str("\nParts adjacent to multiple symbols:\n");
+        for (idx, symbols) in self.shared_parts() {
+            let part = &self.parts[idx];
+            let symbols = symbols
+                .iter()
+                .map(|symbol| format!("{} ({}, {})", symbol.symbol, symbol.coord.x, symbol.coord.y))
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push_str(&format!(
+                "  {} at ({}, {}): {}\n",
+                part.number, part.start.x, part.start.y, symbols
+            ));
+        }
+
+        out.push_str(&format!(
+            "\nGear ratio ('{}' with {} parts): {}",
+            rule.symbol,
+            rule.part_count,
+            self.calc_gear_ratio(rule)
+        ));
+
+        out
+    }
+
+    /// Classifies every cell, expects `mark_neighbor_parts` to have been run.
+    fn classify(&self) -> Grid<CellKind> {
+        let mut kinds = self.grid.map(|_| CellKind::Empty);
+
+        for part in &self.parts {
+            let kind = if part.is_neighbor {
+                CellKind::CountedPart
+            } else {
+                CellKind::IsolatedPart
+            };
+
+            for x in part.start.x..=part.end.x {
+                kinds[Coord { x, y: part.start.y }] = kind;
+            }
+        }
+
+        for symbol in &self.symbols {
+            kinds[symbol.coord] = if self.is_gear(symbol, GearRule::default()) {
+                CellKind::Gear
+            } else {
+                CellKind::Symbol
+            };
+        }
+
+        kinds
+    }
+
+    /// Draws the schematic with ANSI colours, followed by a legend.
+    fn render(&self) -> String {
+        let kinds = self.classify();
+        let mut out = String::new();
+
+        for (coord, c) in self.grid.iter() {
+            if coord.x == 0 && coord.y > 0 {
+                out.push('\n');
+            }
+
+            match kinds[coord].color() {
+                Some(color) => out.push_str(&format!("{}{}{}", color, c, CellKind::RESET)),
+                None => out.push(*c),
+            }
+        }
+
+        let legend = [
+            (CellKind::CountedPart, "counted part"),
+            (CellKind::IsolatedPart, "isolated number"),
+            (CellKind::Symbol, "symbol"),
+            (CellKind::Gear, "gear"),
+        ]
+        .iter()
+        .map(|(kind, label)| format!("{}{}{}", kind.color().unwrap(), label, CellKind::RESET))
+        .collect::<Vec<_>>()
+        .join("  ");
+
+        format!("{}\n\n{}", out, legend)
+    }
 }
 
 pub struct Day3;
 
 impl Day3 {
     fn parse_grid(&self, input: &str) -> Schematic {
+        // Lines are trimmed so indented inputs parse as is, and short rows are
+        // padded out with empty cells rather than rejected.
+        let lines = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let cells = lines
+            .iter()
+            .flat_map(|line| line.chars().chain(iter::repeat('.')).take(width))
+            .collect::<Vec<_>>();
+        let grid = Grid::new(width, lines.len(), cells);
         let mut parts = Vec::new();
         let mut symbols = Vec::new();
 
-        input.lines().enumerate().for_each(|(y, line)| {
+        grid.rows().enumerate().for_each(|(y, row)| {
             let mut start = Coord::default();
-            let mut is_num = false;
-            let mut cur_num = 0;
+            let mut cur_num = None;
 
-            let line = line.trim();
-            line.chars().enumerate().for_each(|(x, c)| match c {
+            row.iter().enumerate().for_each(|(x, c)| match c {
                 '0'..='9' => {
                     let number = c.to_digit(10).unwrap() as usize;
 
-                    if !is_num {
-                        start = Coord { x, y };
-                        is_num = true;
-                        cur_num = number;
-                    } else {
-                        cur_num = cur_num * 10 + number;
-                    }
-
-                    let end = line.chars().nth(x + 1).map_or_else(
-                        || Some(Coord { x, y }),
-                        |next_c| {
-                            if !next_c.is_ascii_digit() {
-                                Some(Coord { x, y })
-                            } else {
-                                None
-                            }
-                        },
-                    );
-
-                    if let Some(end) = end {
-                        parts.push(Part::new(cur_num, start, end));
-
-                        is_num = false;
+                    cur_num = match cur_num {
+                        None => {
+                            start = Coord { x, y };
+                            Some(number)
+                        }
+                        Some(cur_num) => Some(cur_num * 10 + number),
+                    };
+
+                    let at_end = row.get(x + 1).is_none_or(|next_c| !next_c.is_ascii_digit());
+                    if at_end {
+                        parts.push(Part::new(cur_num.take().unwrap(), start, Coord { x, y }));
                     }
                 }
                 // Ignore '.'
@@ -183,12 +345,32 @@ impl Day3 {
                 _ => {
                     let coord = Coord { x, y };
 
-                    symbols.push(Symbol { symbol: c, coord });
+                    symbols.push(Symbol { symbol: *c, coord });
                 }
             });
         });
 
-        Schematic::new(parts, symbols)
+        Schematic::new(grid, parts, symbols)
+    }
+
+    /// Renders the parsed schematic with counted parts, isolated numbers,
+    /// symbols and gears highlighted.
+    pub fn render(&self, input: &str) -> String {
+        let mut schematic = self.parse_grid(input);
+
+        schematic.mark_neighbor_parts();
+        schematic.render()
+    }
+
+    /// Per-symbol statistics, with gears defined by `gear_symbol` touching
+    /// exactly `gear_parts` parts.
+    pub fn symbol_stats(&self, input: &str, gear_symbol: char, gear_parts: usize) -> String {
+        let schematic = self.parse_grid(input);
+
+        schematic.stats(GearRule {
+            symbol: gear_symbol,
+            part_count: gear_parts,
+        })
     }
 }
 
@@ -206,9 +388,9 @@ impl Day for Day3 {
     }
 
     fn part2(&self, input: &str) -> String {
-        let mut schematic = self.parse_grid(input);
+        let schematic = self.parse_grid(input);
 
-        schematic.calc_gear_ratio().to_string()
+        schematic.calc_gear_ratio(GearRule::default()).to_string()
     }
 }
 
@@ -241,4 +423,67 @@ mod test {
 
         assert_eq!(day.part2(INPUT), "467835");
     }
+
+    #[test]
+    fn test_classify() {
+        let mut schematic = Day3.parse_grid(INPUT);
+        schematic.mark_neighbor_parts();
+        let kinds = schematic.classify();
+
+        assert_eq!(kinds[Coord::new(0, 0)], CellKind::CountedPart);
+        assert_eq!(kinds[Coord::new(5, 0)], CellKind::IsolatedPart);
+        assert_eq!(kinds[Coord::new(3, 1)], CellKind::Gear);
+        assert_eq!(kinds[Coord::new(3, 4)], CellKind::Symbol);
+        assert_eq!(kinds[Coord::new(0, 1)], CellKind::Empty);
+    }
+
+    #[test]
+    fn test_symbol_queries() {
+        let schematic = Day3.parse_grid(INPUT);
+        let numbers = |parts: &[usize]| {
+            parts
+                .iter()
+                .map(|idx| schematic.parts[*idx].number)
+                .collect::<Vec<_>>()
+        };
+
+        let by_kind = schematic.parts_by_symbol_kind();
+        assert_eq!(numbers(&by_kind[&'*']), vec![467, 35, 617, 755, 598]);
+        assert_eq!(numbers(&by_kind[&'#']), vec![633]);
+        assert_eq!(schematic.symbols_with_part_count(2).len(), 2);
+        assert!(schematic.shared_parts().is_empty());
+
+        let rule = GearRule {
+            symbol: '#',
+            part_count: 1,
+        };
+        assert_eq!(schematic.calc_gear_ratio(rule), 633);
+
+        let schematic = Day3.parse_grid("..*..\n.123.\n..#..");
+        let shared = schematic.shared_parts();
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].1.len(), 2);
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        let day = Day3;
+        let input = "467..114..\n...*......\n..35";
+
+        assert_eq!(day.part1(input), "502");
+        assert_eq!(day.part2(input), "16345");
+    }
+
+    #[test]
+    fn test_large_schematic() {
+        let day = Day3;
+        let (tiles, rows) = (200, 200);
+        let input = (0..rows)
+            .map(|_| format!("{}\n{}", "12*34.".repeat(tiles), "......".repeat(tiles)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert_eq!(day.part1(&input), (46 * tiles * rows).to_string());
+        assert_eq!(day.part2(&input), (408 * tiles * rows).to_string());
+    }
 }
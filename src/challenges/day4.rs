@@ -1,5 +1,6 @@
 use std::cmp::min;
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 
 use crate::day::Day;
@@ -7,7 +8,7 @@ use crate::day::Day;
 #[derive(Debug)]
 struct Card {
     card_num: usize,
-    winning_numbers: HashSet<usize>,
+    winning_numbers: Vec<usize>,
     numbers: Vec<usize>,
 }
 
@@ -21,24 +22,25 @@ impl Card {
 }
 
 impl FromStr for Card {
-    type Err = ();
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.split('|').collect::<Vec<_>>();
+        let (header, lists) = s
+            .split_once(':')
+            .ok_or("expected `Card N: <winning> | <numbers>`")?;
+        let (winning_numbers_str, numbers_str) = lists
+            .split_once('|')
+            .ok_or("missing `|` between winning numbers and numbers")?;
 
-        let card_num_str = parts[0]
-            .split(':')
-            .nth(0)
-            .unwrap()
-            .split_whitespace()
-            .nth(1)
-            .unwrap();
+        let card_num = match header.split_whitespace().collect::<Vec<_>>()[..] {
+            ["Card", num] => num
+                .parse::<usize>()
+                .map_err(|_| format!("invalid card number `{}`", num))?,
+            _ => return Err(format!("invalid card header `{}`", header.trim())),
+        };
 
-        let card_num = card_num_str.parse::<usize>().unwrap() - 1;
-        let winning_numbers_str = parts[0].split(':').nth(1).unwrap();
-        let winning_numbers = get_numbers(winning_numbers_str).collect();
-
-        let numbers = get_numbers(parts[1]).collect();
+        let winning_numbers = get_numbers(winning_numbers_str)?;
+        let numbers = get_numbers(numbers_str)?;
 
         Ok(Card {
             card_num,
@@ -48,15 +50,45 @@ impl FromStr for Card {
     }
 }
 
-fn get_numbers(s: &str) -> impl Iterator<Item = usize> + '_ {
-    s.trim().split(' ').filter_map(|n| n.parse::<usize>().ok())
+fn get_numbers(s: &str) -> Result<Vec<usize>, String> {
+    s.split_whitespace()
+        .map(|n| {
+            n.parse::<usize>()
+                .map_err(|_| format!("invalid number `{}`", n))
+        })
+        .collect()
+}
+
+/// A problem found on a specific line of the scratchcard input.
+#[derive(Debug, PartialEq, Eq)]
+struct CardError {
+    line: usize,
+    message: String,
+}
+
+/// Every problem found while reading the scratchcards.
+#[derive(Debug)]
+struct CardErrors(Vec<CardError>);
+
+impl fmt::Display for CardErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid scratchcards:")?;
+        for err in &self.0 {
+            write!(f, "\n  line {}: {}", err.line, err.message)?;
+        }
+
+        Ok(())
+    }
 }
 
 pub struct Day4;
 
 impl Day for Day4 {
     fn part1(&self, input: &str) -> String {
-        let cards = get_cards(input);
+        let cards = match get_cards(input) {
+            Ok(cards) => cards,
+            Err(err) => return err.to_string(),
+        };
 
         cards
             .iter()
@@ -74,33 +106,123 @@ impl Day for Day4 {
     }
 
     fn part2(&self, input: &str) -> String {
-        let cards = get_cards(input);
-
-        let mut card_count = 0;
-        let mut card_pile = cards.iter().collect::<VecDeque<_>>();
-        while let Some(card) = card_pile.pop_front() {
-            card_count += 1;
-            let winning_count = card.get_winning_count();
-            if winning_count == 0 {
-                continue;
-            }
+        let cards = match get_cards(input) {
+            Ok(cards) => cards,
+            Err(err) => return err.to_string(),
+        };
 
-            let new_card_end = min(cards.len(), card.card_num + winning_count + 1);
-            let card_adds = &cards[(card.card_num + 1)..new_card_end];
-            for card in card_adds {
-                card_pile.push_back(card);
-            }
+        match count_copies(&cards) {
+            Some(copies) => copies.to_string(),
+            None => "scratchcard copy count overflows 128 bits".to_string(),
         }
+    }
+}
+
+/// Total number of cards held once every won copy has been processed. Each
+/// card adds its copy count to the cards it wins, so this is linear in the
+/// number of cards times matches. Returns `None` if the total overflows.
+///
+/// Expects cards numbered contiguously from 1, which `get_cards` checks.
+fn count_copies(cards: &[Card]) -> Option<u128> {
+    let mut copies = vec![1u128; cards.len()];
 
-        card_count.to_string()
+    for card in cards {
+        let idx = card.card_num - 1;
+        let won_end = min(cards.len(), idx + card.get_winning_count() + 1);
+        for won in (idx + 1)..won_end {
+            copies[won] = copies[won].checked_add(copies[idx])?;
+        }
     }
+
+    copies
+        .into_iter()
+        .try_fold(0u128, |total, count| total.checked_add(count))
 }
 
-fn get_cards(input: &str) -> Vec<Card> {
+fn get_cards(input: &str) -> Result<Vec<Card>, CardErrors> {
+    let mut cards = Vec::new();
+    let mut errors = Vec::new();
+
     input
         .lines()
-        .map(|line| line.parse::<Card>().unwrap())
-        .collect()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .for_each(|(idx, line)| match line.parse::<Card>() {
+            Ok(card) => cards.push((idx + 1, card)),
+            Err(message) => errors.push(CardError {
+                line: idx + 1,
+                message,
+            }),
+        });
+
+    errors.extend(validate_cards(&cards));
+    errors.sort_by_key(|err| err.line);
+
+    if errors.is_empty() {
+        Ok(cards.into_iter().map(|(_, card)| card).collect())
+    } else {
+        Err(CardErrors(errors))
+    }
+}
+
+/// Checks parsed cards, paired with their line numbers, for problems that
+/// would otherwise give a silently wrong answer.
+fn validate_cards(cards: &[(usize, Card)]) -> Vec<CardError> {
+    let mut errors = Vec::new();
+    let mut seen = HashMap::new();
+    let mut expected_lens = None;
+
+    let mut prev_num = 0;
+
+    for (line, card) in cards {
+        let mut error = |message| {
+            errors.push(CardError {
+                line: *line,
+                message,
+            })
+        };
+
+        if let Some(first_line) = seen.get(&card.card_num) {
+            error(format!(
+                "duplicate card number {} (first seen on line {})",
+                card.card_num, first_line
+            ));
+        } else {
+            seen.insert(card.card_num, *line);
+
+            // Compare with the previous card so a gap is only reported once.
+            if card.card_num != prev_num + 1 {
+                error(format!(
+                    "expected card {}, found card {}",
+                    prev_num + 1,
+                    card.card_num
+                ));
+            }
+            prev_num = card.card_num;
+        }
+
+        for (name, list) in [
+            ("winning number", &card.winning_numbers),
+            ("number", &card.numbers),
+        ] {
+            for (idx, num) in list.iter().enumerate() {
+                if list[..idx].contains(num) {
+                    error(format!("duplicate {} {}", name, num));
+                }
+            }
+        }
+
+        let lens = (card.winning_numbers.len(), card.numbers.len());
+        let (first_line, expected) = *expected_lens.get_or_insert((*line, lens));
+        if lens != expected {
+            error(format!(
+                "has {} winning numbers and {} numbers, expected {} and {} (from line {})",
+                lens.0, lens.1, expected.0, expected.1, first_line
+            ));
+        }
+    }
+
+    errors
 }
 
 #[cfg(test)]
@@ -127,4 +249,56 @@ mod test {
 
         assert_eq!(day.part2(INPUT), "30");
     }
+
+    #[test]
+    fn test_validation() {
+        let input = r"Card 1: 41 48 83 | 83 86  6
+        Card 3: 13 32 20 | 61 30 68
+        Card 3:  1 21 21 | 69 82 63
+        Card 4: 41 92 | 59 84 76
+        Card 5: 87 83 26 28 32";
+
+        let errors = get_cards(input).unwrap_err().0;
+        let lines = errors.iter().map(|err| err.line).collect::<Vec<_>>();
+
+        assert_eq!(lines, vec![2, 3, 3, 4, 5]);
+        assert_eq!(errors[0].message, "expected card 2, found card 3");
+        assert_eq!(
+            errors[1].message,
+            "duplicate card number 3 (first seen on line 2)"
+        );
+        assert_eq!(errors[2].message, "duplicate winning number 21");
+        assert!(Day4.part2(input).starts_with("invalid scratchcards:"));
+
+        let input = "Card 1: 1 | 2\nCard 3: 1 | 2\nCard 4: 1 | 2";
+        let errors = get_cards(input).unwrap_err().0;
+        assert_eq!(
+            errors,
+            vec![CardError {
+                line: 2,
+                message: "expected card 2, found card 3".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_huge_copy_counts() {
+        // Every card wins the next two, so copy counts grow like Fibonacci.
+        let input = |count: usize| {
+            (1..=count)
+                .map(|num| format!("Card {}: 1 2 | 1 2", num))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let cards = get_cards(&input(100)).unwrap();
+        assert_eq!(count_copies(&cards), Some(2427893228399975082350));
+
+        let cards = get_cards(&input(200)).unwrap();
+        assert_eq!(count_copies(&cards), None);
+        assert_eq!(
+            Day4.part2(&input(200)),
+            "scratchcard copy count overflows 128 bits"
+        );
+    }
 }
@@ -1,124 +1,479 @@
-use std::{ops::Range, str::FromStr};
-
-use regex::Regex;
+use std::collections::{HashMap, VecDeque};
+use std::{fmt, ops::Range, str::FromStr};
 
 use crate::day::Day;
+use crate::intervals::{fmt_range, IntervalMap, IntervalSet};
+
+/// A single `X-to-Y map:` section of the almanac.
+#[derive(Debug)]
+struct CategoryMap {
+    source: String,
+    destination: String,
+    ranges: RangeMap,
+}
+
+/// One `destination source length` line of an almanac map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MapLine {
+    src: Range<usize>,
+    dst: Range<usize>,
+    /// Line number in the almanac, for diagnostics.
+    line: usize,
+}
+
+impl MapLine {
+    fn offset(&self) -> isize {
+        self.dst.start as isize - self.src.start as isize
+    }
+}
+
+impl fmt::Display for MapLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} ({} {} {})",
+            self.line,
+            self.dst.start,
+            self.src.start,
+            self.src.len()
+        )
+    }
+}
+
+/// The lines of one almanac map. A value uses the first line whose source
+/// range contains it and maps to itself if there is none.
+#[derive(Debug, Default)]
+struct RangeMap {
+    lines: Vec<MapLine>,
+}
+
+impl RangeMap {
+    fn find(&self, val: usize) -> Option<&MapLine> {
+        self.lines.iter().find(|line| line.src.contains(&val))
+    }
+
+    /// Pairs of lines whose ranges, picked by `range`, intersect.
+    fn overlaps<F>(&self, range: F) -> Vec<(&MapLine, &MapLine, Range<usize>)>
+    where
+        F: Fn(&MapLine) -> &Range<usize>,
+    {
+        let mut overlaps = Vec::new();
+
+        for (idx, first) in self.lines.iter().enumerate() {
+            for second in &self.lines[idx + 1..] {
+                let (a, b) = (range(first), range(second));
+                let overlap = a.start.max(b.start)..a.end.min(b.end);
+                if !overlap.is_empty() {
+                    overlaps.push((first, second, overlap));
+                }
+            }
+        }
+
+        overlaps
+    }
+
+    /// Source values no line covers, which map to themselves.
+    fn gaps(&self) -> IntervalSet {
+        let covered = self.lines.iter().map(|line| line.src.clone()).collect();
+
+        IntervalSet::full().difference(&covered)
+    }
+
+    /// The map as a single function, where the first matching line wins and
+    /// unmapped values map to themselves.
+    fn to_interval_map(&self) -> IntervalMap {
+        IntervalMap::from_pieces(
+            self.lines
+                .iter()
+                .map(|line| (line.src.clone(), line.offset())),
+        )
+    }
+
+    /// Overlapping lines make the map order dependent or non-injective, which
+    /// is almost always a mistake in the input.
+    fn warnings(&self) -> Vec<String> {
+        let sources = self.overlaps(|line| &line.src);
+        let destinations = self.overlaps(|line| &line.dst);
+
+        sources
+            .into_iter()
+            .map(|overlap| ("source", overlap))
+            .chain(
+                destinations
+                    .into_iter()
+                    .map(|overlap| ("destination", overlap)),
+            )
+            .map(|(kind, (first, second, overlap))| {
+                format!(
+                    "lines {} and {} have overlapping {} ranges on {}",
+                    first.line,
+                    second.line,
+                    kind,
+                    fmt_range(&overlap)
+                )
+            })
+            .collect()
+    }
+}
 
 #[derive(Debug, Default)]
 struct SeedMap {
     seeds: Vec<usize>,
-    seed_to_soil: Vec<(Range<usize>, Range<usize>)>,
-    soil_to_fertilizer: Vec<(Range<usize>, Range<usize>)>,
-    fertilizer_to_water: Vec<(Range<usize>, Range<usize>)>,
-    water_to_light: Vec<(Range<usize>, Range<usize>)>,
-    light_to_temperature: Vec<(Range<usize>, Range<usize>)>,
-    temperature_to_humidity: Vec<(Range<usize>, Range<usize>)>,
-    humidity_to_location: Vec<(Range<usize>, Range<usize>)>,
+    maps: Vec<CategoryMap>,
+}
+
+impl SeedMap {
+    /// The chain of maps leading from `source` to `destination`, found with a
+    /// breadth first search over the categories named in the map headers.
+    fn path(&self, source: &str, destination: &str) -> Option<Vec<&CategoryMap>> {
+        let mut came_from = HashMap::<&str, &CategoryMap>::new();
+        let mut queue = VecDeque::from([source]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == destination {
+                let mut path = Vec::new();
+                let mut cur = destination;
+                while cur != source {
+                    let map = came_from[cur];
+                    path.push(map);
+                    cur = &map.source;
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            for map in self.maps.iter().filter(|map| map.source == category) {
+                let next = map.destination.as_str();
+                if next != source && !came_from.contains_key(next) {
+                    came_from.insert(next, map);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Converts `value` from the `source` category to `destination`, if the
+    /// almanac connects them.
+    fn convert(&self, value: usize, source: &str, destination: &str) -> Option<usize> {
+        self.path(source, destination)
+            .map(|path| get_seed_location(value, &path))
+    }
+
+    /// Collapses the maps from `source` to `destination` into one function.
+    fn compose(&self, source: &str, destination: &str) -> Option<IntervalMap> {
+        self.path(source, destination).map(|path| {
+            path.iter().fold(IntervalMap::identity(), |composed, map| {
+                composed.then(&map.ranges.to_interval_map())
+            })
+        })
+    }
+
+    /// The seeds line read as pairs of start and length.
+    fn seed_ranges(&self) -> IntervalSet {
+        self.seeds
+            .chunks(2)
+            .map(|chunk| chunk[0]..(chunk[0] + chunk[1]))
+            .collect()
+    }
+
+    fn location_path(&self) -> Result<Vec<&CategoryMap>, String> {
+        self.path("seed", "location")
+            .ok_or_else(|| "No path from seed to location in almanac".to_string())
+    }
+
+    /// The lowest location any seed reaches, treating the seeds line as pairs
+    /// of ranges when `ranges` is set.
+    fn min_location(&self, ranges: bool) -> Result<usize, String> {
+        let path = self.location_path()?;
+
+        let location = if !ranges {
+            self.seeds
+                .iter()
+                .map(|seed| get_seed_location(*seed, &path))
+                .min()
+        } else {
+            path.iter()
+                .fold(self.seed_ranges(), |ranges, map| {
+                    map.ranges.to_interval_map().image(&ranges)
+                })
+                .min()
+        };
+
+        location.ok_or_else(|| "No seeds in almanac".to_string())
+    }
+
+    /// The seed with the lowest location, treating the seeds line as pairs of
+    /// ranges when `ranges` is set.
+    fn min_seed(&self, ranges: bool) -> Result<usize, String> {
+        let path = self.location_path()?;
+
+        let seed = if !ranges {
+            self.seeds
+                .iter()
+                .copied()
+                .min_by_key(|seed| get_seed_location(*seed, &path))
+        } else {
+            // The composed function only increases within a segment, so each
+            // segment's best seed is the first one in range.
+            let composed = path.iter().fold(IntervalMap::identity(), |composed, map| {
+                composed.then(&map.ranges.to_interval_map())
+            });
+            let seeds = self.seed_ranges();
+            composed
+                .segments()
+                .iter()
+                .filter_map(|seg| seeds.intersection(&seg.domain.clone().into()).min())
+                .min_by_key(|seed| composed.get(*seed))
+        };
+
+        seed.ok_or_else(|| "No seeds in almanac".to_string())
+    }
+}
+
+/// One stage of a seed's trip through the almanac.
+#[derive(Debug)]
+struct TraceStep<'a> {
+    map: &'a CategoryMap,
+    /// The line that mapped the value, `None` for the identity fallback.
+    line: Option<&'a MapLine>,
+    value: usize,
+}
+
+fn trace_seed<'a>(seed: usize, path: &[&'a CategoryMap]) -> Vec<TraceStep<'a>> {
+    let mut value = seed;
+
+    path.iter()
+        .map(|map| {
+            let line = map.ranges.find(value);
+            value = get_from_range(value, &map.ranges);
+
+            TraceStep { map, line, value }
+        })
+        .collect()
 }
 
 impl FromStr for SeedMap {
-    type Err = ();
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let seeds_regex = Regex::new(r"seeds:\s*([\d\s]+)").unwrap();
-        let caps = &seeds_regex.captures(s).unwrap()[1];
-        let seeds = caps
-            .split_whitespace()
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
-
-        let seed_to_soil = capture_map(s, "seed-to-soil");
-        let soil_to_fertilizer = capture_map(s, "soil-to-fertilizer");
-        let fertilizer_to_water = capture_map(s, "fertilizer-to-water");
-        let water_to_light = capture_map(s, "water-to-light");
-        let light_to_temperature = capture_map(s, "light-to-temperature");
-        let temperature_to_humidity = capture_map(s, "temperature-to-humidity");
-        let humidity_to_location = capture_map(s, "humidity-to-location");
+        let mut seeds = None;
+        let mut maps = Vec::<CategoryMap>::new();
+
+        for (idx, line) in s.lines().map(str::trim).enumerate() {
+            let line_err = |err| format!("line {}: {}", idx + 1, err);
+
+            if line.is_empty() {
+                continue;
+            } else if let Some(nums) = line.strip_prefix("seeds:") {
+                seeds = Some(get_nums(nums).map_err(line_err)?);
+            } else if let Some(header) = line.strip_suffix(" map:") {
+                let (source, destination) = header
+                    .split_once("-to-")
+                    .ok_or_else(|| line_err(format!("invalid map header `{}`", line)))?;
+                maps.push(CategoryMap {
+                    source: source.to_string(),
+                    destination: destination.to_string(),
+                    ranges: RangeMap::default(),
+                });
+            } else {
+                let map = maps
+                    .last_mut()
+                    .ok_or_else(|| line_err("map line before any map header".to_string()))?;
+                let map_line = parse_map_line(line, idx + 1).map_err(line_err)?;
+                map.ranges.lines.push(map_line);
+            }
+        }
 
         Ok(SeedMap {
-            seeds,
-            seed_to_soil,
-            soil_to_fertilizer,
-            fertilizer_to_water,
-            water_to_light,
-            light_to_temperature,
-            temperature_to_humidity,
-            humidity_to_location,
+            seeds: seeds.ok_or("missing `seeds:` line")?,
+            maps,
         })
     }
 }
 
-fn capture_map(input: &str, regex_prefix: &str) -> Vec<(Range<usize>, Range<usize>)> {
-    let regex = Regex::new(&format!(r"{} map:\s*([\d\s]+)", regex_prefix)).unwrap();
-    let cap_str = &regex.captures(input).unwrap()[1];
-
-    cap_str
-        .trim_end()
-        .lines()
-        .map(|line| {
-            let parts = line
-                .split_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect::<Vec<usize>>();
-
-            (
-                parts[1]..(parts[1] + parts[2]),
-                parts[0]..(parts[0] + parts[1]),
-            )
+fn get_nums(s: &str) -> Result<Vec<usize>, String> {
+    s.split_whitespace()
+        .map(|num| {
+            num.parse::<usize>()
+                .map_err(|_| format!("invalid number `{}`", num))
         })
         .collect()
 }
 
-fn get_from_range(val: usize, ranges: &[(Range<usize>, Range<usize>)]) -> usize {
+fn parse_map_line(s: &str, line: usize) -> Result<MapLine, String> {
+    let parts = get_nums(s)?;
+    let [dst, src, len] = parts[..] else {
+        return Err(format!("expected 3 numbers, found {}", parts.len()));
+    };
+
+    // Offsets between the two starts are signed, so both have to fit.
+    if let Some(start) = [dst, src]
+        .into_iter()
+        .find(|start| *start > isize::MAX as usize)
+    {
+        return Err(format!("start {} is larger than {}", start, isize::MAX));
+    }
+
+    let end = |start: usize| {
+        start
+            .checked_add(len)
+            .ok_or_else(|| format!("range {} + {} overflows", start, len))
+    };
+
+    Ok(MapLine {
+        src: src..end(src)?,
+        dst: dst..end(dst)?,
+        line,
+    })
+}
+
+fn get_from_range(val: usize, ranges: &RangeMap) -> usize {
     ranges
-        .iter()
-        .find(|(src, _)| src.contains(&val))
-        .map_or(val, |(src, range)| {
-            let offset = val - src.start;
-            range.start + offset
-        })
+        .find(val)
+        .map_or(val, |line| line.dst.start + (val - line.src.start))
 }
 
-fn get_seed_location(seed: usize, seed_map: &SeedMap) -> usize {
-    let soil = get_from_range(seed, &seed_map.seed_to_soil);
-    let fertilizer = get_from_range(soil, &seed_map.soil_to_fertilizer);
-    let water = get_from_range(fertilizer, &seed_map.fertilizer_to_water);
-    let light = get_from_range(water, &seed_map.water_to_light);
-    let temperature = get_from_range(light, &seed_map.light_to_temperature);
-    let humidity = get_from_range(temperature, &seed_map.temperature_to_humidity);
-    get_from_range(humidity, &seed_map.humidity_to_location)
+fn get_seed_location(seed: usize, path: &[&CategoryMap]) -> usize {
+    path.iter()
+        .fold(seed, |val, map| get_from_range(val, &map.ranges))
 }
 
 pub struct Day5;
 
-impl Day for Day5 {
-    fn part1(&self, input: &str) -> String {
-        let seed_map = input.parse::<SeedMap>().unwrap();
+impl Day5 {
+    /// Converts `value` between any two categories the almanac connects, or
+    /// with `reverse` finds every `source` value that produces it.
+    pub fn convert(
+        &self,
+        input: &str,
+        value: usize,
+        source: &str,
+        destination: &str,
+        reverse: bool,
+    ) -> String {
+        let seed_map = match input.parse::<SeedMap>() {
+            Ok(seed_map) => seed_map,
+            Err(err) => return err,
+        };
 
-        seed_map
-            .seeds
-            .iter()
-            .map(|seed| get_seed_location(*seed, &seed_map))
-            .min()
-            .unwrap()
-            .to_string()
+        if !reverse {
+            return match seed_map.convert(value, source, destination) {
+                Some(converted) => format!("{} {} -> {} {}", source, value, destination, converted),
+                None => format!("No path from {} to {} in almanac", source, destination),
+            };
+        }
+
+        let Some(composed) = seed_map.compose(source, destination) else {
+            return format!("No path from {} to {} in almanac", source, destination);
+        };
+
+        let sources = composed.preimage(&IntervalSet::from(value..value + 1));
+        if sources.is_empty() {
+            format!("No {} value produces {} {}", source, destination, value)
+        } else {
+            format!("{} {} <- {} {}", destination, value, source, sources)
+        }
     }
 
-    fn part2(&self, input: &str) -> String {
-        let seed_map = input.parse::<SeedMap>().unwrap();
+    /// Shows the value at every stage from `seed` to its location and which
+    /// map line produced it. Without a seed, traces the one with the lowest
+    /// location, reading the seeds as ranges if `ranges` is set.
+    pub fn trace(&self, input: &str, seed: Option<usize>, ranges: bool) -> String {
+        let seed_map = match input.parse::<SeedMap>() {
+            Ok(seed_map) => seed_map,
+            Err(err) => return err,
+        };
+        let path = match seed_map.location_path() {
+            Ok(path) => path,
+            Err(err) => return err,
+        };
+        let seed = match seed.map_or_else(|| seed_map.min_seed(ranges), Ok) {
+            Ok(seed) => seed,
+            Err(err) => return err,
+        };
+
+        let mut out = format!("seed {}", seed);
+        for step in trace_seed(seed, &path) {
+            let matched = step
+                .line
+                .map_or_else(|| "identity".to_string(), |line| line.to_string());
+            out.push_str(&format!(
+                "\n  {}-to-{}: {} -> {} {}",
+                step.map.source, step.map.destination, matched, step.map.destination, step.value
+            ));
+        }
+
+        out
+    }
+
+    /// Reports, per map, the source ranges left to the identity fallback and
+    /// any overlapping lines.
+    pub fn validate(&self, input: &str) -> String {
+        let seed_map = match input.parse::<SeedMap>() {
+            Ok(seed_map) => seed_map,
+            Err(err) => return err,
+        };
 
         seed_map
-            .seeds
-            .chunks(2)
-            .map(|chunk| {
-                (chunk[0]..(chunk[0] + chunk[1]))
-                    .map(|seed| get_seed_location(seed, &seed_map))
-                    .min()
-                    .unwrap()
+            .maps
+            .iter()
+            .map(|map| {
+                let gaps = map.ranges.gaps();
+                let mut report = format!(
+                    "{}-to-{} ({} lines)\n  identity gaps: {}",
+                    map.source,
+                    map.destination,
+                    map.ranges.lines.len(),
+                    gaps
+                );
+
+                for warning in map.ranges.warnings() {
+                    report.push_str(&format!("\n  warning: {}", warning));
+                }
+
+                report
             })
-            .min()
-            .unwrap()
-            .to_string()
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Prints the maps from `source` to `destination` collapsed into a single
+    /// piecewise function.
+    pub fn compose(&self, input: &str, source: &str, destination: &str) -> String {
+        let seed_map = match input.parse::<SeedMap>() {
+            Ok(seed_map) => seed_map,
+            Err(err) => return err,
+        };
+
+        seed_map.compose(source, destination).map_or_else(
+            || format!("No path from {} to {} in almanac", source, destination),
+            |composed| composed.to_string(),
+        )
+    }
+}
+
+impl Day for Day5 {
+    fn part1(&self, input: &str) -> String {
+        match input
+            .parse::<SeedMap>()
+            .and_then(|seed_map| seed_map.min_location(false))
+        {
+            Ok(location) => location.to_string(),
+            Err(err) => err,
+        }
+    }
+
+    fn part2(&self, input: &str) -> String {
+        match input
+            .parse::<SeedMap>()
+            .and_then(|seed_map| seed_map.min_location(true))
+        {
+            Ok(location) => location.to_string(),
+            Err(err) => err,
+        }
     }
 }
 
@@ -126,6 +481,8 @@ impl Day for Day5 {
 mod test {
     use super::*;
 
+    use crate::test_util::XorShift;
+
     const INPUT: &str = r"seeds: 79 14 55 13
 
 seed-to-soil map:
@@ -173,4 +530,202 @@ humidity-to-location map:
 
         assert_eq!(day.part2(INPUT), "46");
     }
+
+    #[test]
+    fn test_category_queries() {
+        let seed_map = INPUT.parse::<SeedMap>().unwrap();
+
+        assert_eq!(seed_map.convert(79, "seed", "soil"), Some(81));
+        assert_eq!(seed_map.convert(81, "soil", "humidity"), Some(78));
+        assert_eq!(seed_map.convert(79, "seed", "location"), Some(82));
+        assert_eq!(seed_map.convert(82, "location", "seed"), None);
+    }
+
+    #[test]
+    fn test_reordered_and_extra_stages() {
+        let mut sections = INPUT.split("\n\n").collect::<Vec<_>>();
+        sections[1..].reverse();
+        sections.push("location-to-zone map:\n0 80 10");
+        let input = sections.join("\n\n");
+
+        let seed_map = input.parse::<SeedMap>().unwrap();
+        assert_eq!(Day5.part1(&input), "35");
+        assert_eq!(seed_map.convert(79, "seed", "zone"), Some(2));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("seed-to-soil map:\n1 2 3".parse::<SeedMap>().is_err());
+        assert_eq!(
+            "seeds: 1\n\nseed-to-soil map:\n1 2"
+                .parse::<SeedMap>()
+                .unwrap_err(),
+            "line 4: expected 3 numbers, found 2"
+        );
+        assert_eq!(
+            format!(
+                "seeds: 1\n\nseed-to-soil map:\n0 {} 1",
+                isize::MAX as usize + 1
+            )
+            .parse::<SeedMap>()
+            .unwrap_err(),
+            format!(
+                "line 4: start {} is larger than {}",
+                isize::MAX as usize + 1,
+                isize::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn test_missing_stage() {
+        let start = INPUT.find("water-to-light map:").unwrap();
+        let end = INPUT.find("light-to-temperature map:").unwrap();
+        let input = format!("{}{}", &INPUT[..start], &INPUT[end..]);
+        let no_path = "No path from seed to location in almanac";
+
+        assert_eq!(Day5.part1(&input), no_path);
+        assert_eq!(Day5.part2(&input), no_path);
+        assert_eq!(Day5.trace(&input, None, false), no_path);
+        assert_eq!(
+            Day5.part1("seed-to-soil map:\n1 2 3"),
+            "missing `seeds:` line"
+        );
+        assert_eq!(
+            Day5.part1("seeds:\n\nseed-to-location map:\n1 2 3"),
+            "No seeds in almanac"
+        );
+    }
+
+    #[test]
+    fn test_range_map_validation() {
+        let seed_map = INPUT.parse::<SeedMap>().unwrap();
+        let seed_to_soil = &seed_map.maps[0].ranges;
+
+        assert_eq!(seed_to_soil.lines[0].src, 98..100);
+        assert_eq!(seed_to_soil.lines[0].dst, 50..52);
+        assert_eq!(seed_to_soil.lines[0].line, 4);
+        assert_eq!(seed_to_soil.gaps().ranges(), &[0..50, 100..usize::MAX]);
+        assert!(seed_to_soil.warnings().is_empty());
+
+        let input = "seeds: 1\n\na-to-b map:\n0 10 10\n5 15 10\n";
+        let seed_map = input.parse::<SeedMap>().unwrap();
+        assert_eq!(
+            seed_map.maps[0].ranges.warnings(),
+            vec![
+                "lines 4 and 5 have overlapping source ranges on [15, 20)",
+                "lines 4 and 5 have overlapping destination ranges on [5, 10)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let seed_map = INPUT.parse::<SeedMap>().unwrap();
+        let steps = trace_seed(79, &seed_map.location_path().unwrap());
+
+        let values = steps.iter().map(|step| step.value).collect::<Vec<_>>();
+        assert_eq!(values, vec![81, 81, 81, 74, 78, 78, 82]);
+        assert_eq!(steps[0].line.unwrap().line, 5);
+        assert!(steps[1].line.is_none());
+
+        assert_eq!(seed_map.min_seed(false), Ok(13));
+        assert_eq!(seed_map.min_seed(true), Ok(82));
+    }
+
+    #[test]
+    fn test_composition() {
+        let seed_map = INPUT.parse::<SeedMap>().unwrap();
+        let path = seed_map.location_path().unwrap();
+        let composed = seed_map.compose("seed", "location").unwrap();
+        let inverse = composed.invert().unwrap();
+
+        for seed in 0..200 {
+            let location = get_seed_location(seed, &path);
+            assert_eq!(composed.get(seed), location);
+            assert_eq!(inverse.get(location), seed);
+        }
+    }
+
+    #[test]
+    fn test_non_invertible() {
+        // Both 0..10 and 10..20 land on 10..20.
+        let map = RangeMap {
+            lines: vec![MapLine {
+                src: 0..10,
+                dst: 10..20,
+                line: 1,
+            }],
+        }
+        .to_interval_map();
+
+        assert_eq!(map.get(5), 15);
+        assert_eq!(map.get(15), 15);
+        assert!(map.invert().is_none());
+        assert_eq!(
+            map.preimage(&(15..16).into()).to_string(),
+            "[5, 6), [15, 16)"
+        );
+
+        // Reverse queries still work, listing every seed or none.
+        let input = "seeds: 1\n\nseed-to-location map:\n10 0 10";
+        assert_eq!(
+            Day5.convert(input, 15, "seed", "location", true),
+            "location 15 <- seed [5, 6), [15, 16)"
+        );
+        assert_eq!(
+            Day5.convert(input, 5, "seed", "location", true),
+            "No seed value produces location 5"
+        );
+        assert_eq!(
+            Day5.convert(INPUT, 82, "seed", "location", true),
+            "location 82 <- seed [79, 80)"
+        );
+    }
+
+    fn brute_force_part2(seed_map: &SeedMap) -> usize {
+        let path = seed_map.location_path().unwrap();
+
+        seed_map
+            .seeds
+            .chunks(2)
+            .flat_map(|chunk| chunk[0]..(chunk[0] + chunk[1]))
+            .map(|seed| get_seed_location(seed, &path))
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_part2_matches_brute_force() {
+        let mut rng = XorShift::new(0x2545f491);
+        let mut rand = |max: usize| rng.below(max);
+
+        let stages = [
+            "seed-to-soil",
+            "soil-to-fertilizer",
+            "fertilizer-to-water",
+            "water-to-light",
+            "light-to-temperature",
+            "temperature-to-humidity",
+            "humidity-to-location",
+        ];
+
+        for _ in 0..50 {
+            let seeds = (0..4)
+                .map(|_| format!("{} {}", rand(100), rand(30) + 1))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let mut input = format!("seeds: {}\n", seeds);
+
+            for stage in stages {
+                input.push_str(&format!("\n{} map:\n", stage));
+                for _ in 0..(rand(4) + 1) {
+                    input.push_str(&format!("{} {} {}\n", rand(120), rand(120), rand(40) + 1));
+                }
+            }
+
+            let seed_map = input.parse::<SeedMap>().unwrap();
+            assert_eq!(Day5.part2(&input), brute_force_part2(&seed_map).to_string());
+        }
+    }
 }
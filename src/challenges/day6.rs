@@ -34,24 +34,53 @@ fn get_nums(s: &str) -> Vec<usize> {
         .collect::<Vec<_>>()
 }
 
-fn calc_race_win_count(time: usize, distance: usize) -> usize {
-    (0..=time)
-        .filter(|speed| {
-            let remaining_time = time - speed;
-            let distance_traveled = speed * remaining_time;
-
-            distance_traveled > distance
-        })
-        .count()
+/// Number of hold times that beat `distance`. Holding for `h` travels
+/// `h * (time - h)`, so the winners lie strictly between the roots of
+/// `h^2 - time * h + distance`. The integer square root only gets us within
+/// one of the lower root, so nudge it onto the first winning hold time.
+///
+/// Returns `None` if `time` is too big to square in 128 bits.
+fn calc_race_win_count(time: u128, distance: u128) -> Option<u128> {
+    time.checked_mul(time)?;
+    let traveled = |hold: u128| hold * (time - hold);
+
+    // The best hold time is the midpoint, if that can't win nothing can.
+    if traveled(time / 2) <= distance {
+        return Some(0);
+    }
+
+    let discriminant = time * time - 4 * distance;
+    let mut lower = (time - discriminant.isqrt()) / 2;
+    while traveled(lower) <= distance {
+        lower += 1;
+    }
+    while lower > 0 && traveled(lower - 1) > distance {
+        lower -= 1;
+    }
+
+    // Hold times are symmetric around the midpoint.
+    let upper = time - lower;
+    Some(upper - lower + 1)
 }
 
-fn num_from_list(list: &str) -> usize {
-    list.split_whitespace().skip(1).fold(0, |acc, s| {
-        let num = s.parse::<usize>().unwrap();
-        let mul = 10usize.pow(num.ilog10() + 1);
+/// Joins the numbers on a row as if the spaces between them weren't there.
+fn num_from_list(list: &str) -> Result<u128, String> {
+    let digits = list.split_whitespace().skip(1).collect::<String>();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("`{}` is not a row of numbers", list.trim()));
+    }
 
-        acc * mul + num
-    })
+    digits
+        .parse::<u128>()
+        .map_err(|_| format!("{} overflows 128 bits", digits))
+}
+
+fn calc_joined_race(input: &str) -> Result<u128, String> {
+    let mut lines = input.lines();
+    let time = num_from_list(lines.next().ok_or("missing time row")?)?;
+    let distance = num_from_list(lines.next().ok_or("missing distance row")?)?;
+
+    calc_race_win_count(time, distance).ok_or_else(|| format!("race time {} overflows", time))
 }
 
 pub struct Day6;
@@ -62,17 +91,16 @@ impl Day for Day6 {
 
         races
             .iter_races()
-            .map(|(time, distance)| calc_race_win_count(*time, *distance))
-            .product::<usize>()
+            .map(|(time, distance)| calc_race_win_count(*time as u128, *distance as u128).unwrap())
+            .product::<u128>()
             .to_string()
     }
 
     fn part2(&self, input: &str) -> String {
-        let chunks = input.lines().collect::<Vec<_>>();
-        let time = num_from_list(chunks[0]);
-        let distance = num_from_list(chunks[1]);
-
-        calc_race_win_count(time, distance).to_string()
+        match calc_joined_race(input) {
+            Ok(count) => count.to_string(),
+            Err(err) => err,
+        }
     }
 }
 
@@ -96,4 +124,59 @@ mod test {
 
         assert_eq!(day.part2(INPUT), "71503");
     }
+
+    fn brute_force_win_count(time: u128, distance: u128) -> u128 {
+        (0..=time)
+            .filter(|hold| hold * (time - hold) > distance)
+            .count() as u128
+    }
+
+    #[test]
+    fn test_win_count_matches_brute_force() {
+        for time in 0..80 {
+            // Past time^2 / 4 nothing wins, so go a little beyond it.
+            for distance in 0..=(time * time / 4 + 2) {
+                assert_eq!(
+                    calc_race_win_count(time, distance),
+                    Some(brute_force_win_count(time, distance)),
+                    "time {} distance {}",
+                    time,
+                    distance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_huge_race() {
+        let time = 10u128.pow(18);
+        // Exact tie at hold times 10^17 and 9 * 10^17, which don't count.
+        let distance = 10u128.pow(17) * 9 * 10u128.pow(17);
+
+        assert_eq!(
+            calc_race_win_count(time, distance),
+            Some(8 * 10u128.pow(17) - 1)
+        );
+    }
+
+    #[test]
+    fn test_joined_numbers() {
+        assert_eq!(num_from_list("Time:  7  0  05"), Ok(7005));
+        assert!(num_from_list("Time: 7 x").is_err());
+
+        let long_row = format!("Time: {}", "1234567890 ".repeat(4));
+        assert_eq!(
+            num_from_list(&long_row),
+            Err(format!("{} overflows 128 bits", "1234567890".repeat(4)))
+        );
+
+        let input = format!("Time: {}\nDistance: 1", 1u128 << 65);
+        assert_eq!(
+            calc_joined_race(&input),
+            Err(format!("race time {} overflows", 1u128 << 65))
+        );
+
+        assert_eq!(Day6.part2(""), "missing time row");
+        assert_eq!(Day6.part2("Time: 7"), "missing distance row");
+    }
 }
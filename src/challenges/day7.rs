@@ -2,120 +2,275 @@ use std::cmp::Reverse;
 
 use crate::day::Day;
 
-#[derive(Debug)]
-enum CardRule {
-    Normal(char),
-    Joker(char),
+/// A hand category, matched when the hand's card counts, sorted from most to
+/// least common, cover `pattern`.
+#[derive(Debug, Clone)]
+struct Category {
+    name: &'static str,
+    pattern: Vec<usize>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct Card(usize);
-
-impl From<CardRule> for Card {
-    fn from(value: CardRule) -> Self {
-        let card = match value {
-            CardRule::Normal('T') | CardRule::Joker('T') => 10,
-            CardRule::Normal('J') => 11,
-            CardRule::Joker('J') => 1,
-            CardRule::Normal('Q') | CardRule::Joker('Q') => 12,
-            CardRule::Normal('K') | CardRule::Joker('K') => 13,
-            CardRule::Normal('A') | CardRule::Joker('A') => 14,
-            CardRule::Normal(c) | CardRule::Joker(c) => c.to_digit(10).unwrap() as usize,
-        };
+impl Category {
+    fn new(name: &'static str, pattern: &[usize]) -> Self {
+        Self {
+            name,
+            pattern: pattern.to_vec(),
+        }
+    }
 
-        Self(card)
+    fn matches(&self, counts: &[usize]) -> bool {
+        self.pattern.len() <= counts.len()
+            && self
+                .pattern
+                .iter()
+                .zip(counts)
+                .all(|(want, have)| have >= want)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
-    FiveOfAKind,
-    FourOfAKind,
-    FullHouse,
-    ThreeOfAKind,
-    TwoPair,
-    OnePair,
-    HighCard,
+/// Everything that varies between card games: card strength, which cards are
+/// wild, how many cards make a hand and which hands beat which.
+#[derive(Debug, Clone)]
+struct Rules {
+    /// Card labels from weakest to strongest.
+    card_order: Vec<char>,
+    /// Cards that stand in for whichever card makes the best hand.
+    wildcards: Vec<char>,
+    hand_size: usize,
+    /// Categories from strongest to weakest, a hand gets the first that matches.
+    categories: Vec<Category>,
 }
 
-impl HandType {
-    fn new(cards: &[Card]) -> Self {
-        let mut counts = [0; 15];
+impl Rules {
+    fn poker_categories() -> Vec<Category> {
+        vec![
+            Category::new("Five of a kind", &[5]),
+            Category::new("Four of a kind", &[4]),
+            Category::new("Full house", &[3, 2]),
+            Category::new("Three of a kind", &[3]),
+            Category::new("Two pair", &[2, 2]),
+            Category::new("One pair", &[2]),
+            Category::new("High card", &[1]),
+        ]
+    }
+
+    fn normal() -> Self {
+        Self {
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            hand_size: 5,
+            categories: Self::poker_categories(),
+        }
+    }
+
+    /// Jacks become jokers: wild, but the weakest card when breaking ties.
+    fn joker() -> Self {
+        Self {
+            card_order: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            ..Self::normal()
+        }
+    }
+
+    fn card(&self, label: char) -> Result<Card, String> {
+        self.card_order
+            .iter()
+            .position(|c| *c == label)
+            .map(Card)
+            .ok_or_else(|| format!("unknown card `{}`", label))
+    }
+
+    fn is_wild(&self, card: &Card) -> bool {
+        self.wildcards.contains(&self.card_order[card.0])
+    }
+
+    /// The category for a set of cards, where `counts[rank]` is how many of
+    /// each card the hand holds.
+    fn category(&self, counts: &[usize]) -> HandType {
+        let mut counts = counts
+            .iter()
+            .copied()
+            .filter(|count| *count > 0)
+            .collect::<Vec<_>>();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+
+        let idx = self
+            .categories
+            .iter()
+            .position(|category| category.matches(&counts))
+            .unwrap_or(self.categories.len());
+
+        HandType(idx)
+    }
+
+    fn label(&self, card: &Card) -> char {
+        self.card_order[card.0]
+    }
+
+    /// The category's name, or "Unranked" for hands no category matches.
+    fn category_name(&self, hand_type: HandType) -> &'static str {
+        self.categories
+            .get(hand_type.0)
+            .map_or("Unranked", |category| category.name)
+    }
+
+    /// The wildcards a hand holds and the card they stand in for, like `J=5`,
+    /// or `-` if it holds none.
+    fn substitution(&self, hand: &Hand) -> String {
+        let Some(substitute) = hand.substitute else {
+            return "-".to_string();
+        };
+
+        let held = self
+            .wildcards
+            .iter()
+            .filter(|wild| hand.cards.iter().any(|card| self.label(card) == **wild))
+            .collect::<String>();
+
+        format!("{}={}", held, self.label(&substitute))
+    }
+
+    /// Classifies a hand, giving the wildcards whichever identity makes the
+    /// best hand, and returns the card they stand in for if there are any.
+    /// Every category is about groups of equal cards, so the best substitution
+    /// always turns all the wildcards into one of the natural cards already
+    /// held, or the strongest card if they're all wild.
+    fn best_substitution(&self, cards: &[Card]) -> (HandType, Option<Card>) {
+        let mut counts = vec![0; self.card_order.len()];
+        let mut wild_count = 0;
         for card in cards {
-            counts[card.0] += 1;
+            if self.is_wild(card) {
+                wild_count += 1;
+            } else {
+                counts[card.0] += 1;
+            }
         }
 
-        let joker_count = counts[1];
-
-        counts.sort_by_key(|count| Reverse(*count));
-        match (counts[0], counts[1]) {
-            (5, _) => Self::FiveOfAKind,
-            (4, _) => match joker_count {
-                4 | 1 => Self::FiveOfAKind,
-                _ => Self::FourOfAKind,
-            },
-            (3, 2) => match joker_count {
-                3 | 2 => Self::FiveOfAKind,
-                _ => Self::FullHouse,
-            },
-            (3, _) => match joker_count {
-                1 => Self::FourOfAKind,
-                2 => Self::FiveOfAKind,
-                3 => Self::FourOfAKind,
-                _ => Self::ThreeOfAKind,
-            },
-            (2, 2) => match joker_count {
-                1 => Self::FullHouse,
-                2 => Self::FourOfAKind,
-                _ => Self::TwoPair,
-            },
-            (2, _) => match joker_count {
-                1 | 2 => Self::ThreeOfAKind,
-                _ => Self::OnePair,
-            },
-            _ => match joker_count {
-                1 => Self::OnePair,
-                _ => Self::HighCard,
-            },
+        let mut candidates = (0..counts.len())
+            .filter(|rank| counts[*rank] > 0)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            candidates.push(counts.len() - 1);
         }
+
+        // Ties go to the strongest card, which reads most naturally.
+        let (hand_type, substitute) = candidates
+            .into_iter()
+            .map(|rank| {
+                let mut counts = counts.clone();
+                counts[rank] += wild_count;
+                (self.category(&counts), Card(rank))
+            })
+            .min_by_key(|(hand_type, card)| (*hand_type, Reverse(*card)))
+            .unwrap();
+
+        debug_assert_eq!(
+            hand_type,
+            self.classify_brute_force(cards),
+            "Wildcard shortcut disagrees with brute force for {:?}",
+            cards
+        );
+
+        (hand_type, (wild_count > 0).then_some(substitute))
+    }
+
+    /// Classifies a hand by trying every natural card in place of every
+    /// wildcard. Exponential in the number of wildcards, so it's only used to
+    /// cross-check `best_substitution` in debug builds.
+    fn classify_brute_force(&self, cards: &[Card]) -> HandType {
+        let naturals = (0..self.card_order.len())
+            .filter(|rank| !self.is_wild(&Card(*rank)))
+            .collect::<Vec<_>>();
+
+        let mut counts = vec![0; self.card_order.len()];
+        let mut wild_count = 0;
+        for card in cards {
+            if self.is_wild(card) && !naturals.is_empty() {
+                wild_count += 1;
+            } else {
+                counts[card.0] += 1;
+            }
+        }
+
+        self.best_replacement(&mut counts, wild_count, &naturals)
+    }
+
+    /// Wildcards are interchangeable, so replacements are only tried in
+    /// non-decreasing rank order to skip permutations of the same choice.
+    fn best_replacement(
+        &self,
+        counts: &mut [usize],
+        wild_count: usize,
+        naturals: &[usize],
+    ) -> HandType {
+        if wild_count == 0 {
+            return self.category(counts);
+        }
+
+        (0..naturals.len())
+            .map(|idx| {
+                counts[naturals[idx]] += 1;
+                let hand_type = self.best_replacement(counts, wild_count - 1, &naturals[idx..]);
+                counts[naturals[idx]] -= 1;
+
+                hand_type
+            })
+            .min()
+            .unwrap()
     }
 }
 
+/// A card's strength, as its index into [`Rules::card_order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Card(usize);
+
+/// A hand's category, as its index into [`Rules::categories`], so lower is
+/// stronger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct HandType(usize);
+
 #[derive(Debug)]
 struct Hand {
     cards: Vec<Card>,
     hand_type: HandType,
+    /// What the wildcards were counted as, if the hand has any.
+    substitute: Option<Card>,
     bid: usize,
 }
 
 impl Hand {
-    fn new(s: &str, joker: bool) -> Self {
+    fn new(s: &str, rules: &Rules) -> Result<Self, String> {
         let parts = s.split_whitespace().collect::<Vec<_>>();
+        let [cards, bid] = parts[..] else {
+            return Err(format!("expected `<cards> <bid>`, found `{}`", s.trim()));
+        };
 
-        let bid = parts[1].parse::<usize>().unwrap();
-
-        let cards = build_cards(parts[0], joker);
+        let bid = bid
+            .parse::<usize>()
+            .map_err(|_| format!("invalid bid `{}`", bid))?;
+
+        let cards = build_cards(cards, rules)?;
+        if cards.len() != rules.hand_size {
+            return Err(format!(
+                "expected {} cards, found {}",
+                rules.hand_size,
+                cards.len()
+            ));
+        }
 
-        let hand_type = HandType::new(cards.as_slice());
+        let (hand_type, substitute) = rules.best_substitution(cards.as_slice());
 
-        Self {
+        Ok(Self {
             cards,
             hand_type,
+            substitute,
             bid,
-        }
+        })
     }
 }
 
-fn build_cards(s: &str, joker: bool) -> Vec<Card> {
-    if joker {
-        s.chars()
-            .map(|c| Card::from(CardRule::Joker(c)))
-            .collect::<Vec<_>>()
-    } else {
-        s.chars()
-            .map(|c| Card::from(CardRule::Normal(c)))
-            .collect::<Vec<_>>()
-    }
+fn build_cards(s: &str, rules: &Rules) -> Result<Vec<Card>, String> {
+    s.chars().map(|c| rules.card(c)).collect()
 }
 
 impl PartialEq for Hand {
@@ -146,29 +301,116 @@ impl Ord for Hand {
 pub struct Day7;
 
 impl Day7 {
-    fn calc_winnings(input: &str, joker: bool) -> usize {
+    /// Hands from weakest to strongest, so a hand's rank is its index + 1.
+    fn ranked_hands(input: &str, rules: &Rules) -> Result<Vec<Hand>, String> {
         let mut hands = input
             .lines()
-            .map(|line| Hand::new(line, joker))
-            .collect::<Vec<_>>();
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                Hand::new(line, rules).map_err(|err| format!("line {}: {}", idx + 1, err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
         hands.sort();
         hands.reverse();
 
-        hands
+        Ok(hands)
+    }
+
+    fn calc_winnings(input: &str, rules: &Rules) -> Result<usize, String> {
+        let winnings = Self::ranked_hands(input, rules)?
             .iter()
             .enumerate()
             .map(|(idx, hand)| (idx + 1) * hand.bid)
-            .sum::<usize>()
+            .sum::<usize>();
+
+        Ok(winnings)
+    }
+
+    /// Lists every hand in rank order with its type, wildcard substitution,
+    /// bid and winnings, as an aligned table or as CSV.
+    pub fn report(&self, input: &str, joker: bool, csv: bool) -> String {
+        let rules = if joker {
+            Rules::joker()
+        } else {
+            Rules::normal()
+        };
+        let hands = match Self::ranked_hands(input, &rules) {
+            Ok(hands) => hands,
+            Err(err) => return err,
+        };
+
+        let rows = hands.iter().enumerate().map(|(idx, hand)| {
+            let rank = idx + 1;
+            let cards = hand
+                .cards
+                .iter()
+                .map(|card| rules.label(card))
+                .collect::<String>();
+            let substitution = rules.substitution(hand);
+            let hand_type = rules.category_name(hand.hand_type);
+
+            if csv {
+                format!(
+                    "{},{},{},{},{},{}",
+                    rank,
+                    cards,
+                    hand_type,
+                    substitution,
+                    hand.bid,
+                    rank * hand.bid
+                )
+            } else {
+                format!(
+                    "{:>5}  {:<6} {:<16} {:<5} {:>5}  {:>9}",
+                    rank,
+                    cards,
+                    hand_type,
+                    substitution,
+                    hand.bid,
+                    rank * hand.bid
+                )
+            }
+        });
+
+        if csv {
+            std::iter::once("rank,hand,type,substitution,bid,winnings".to_string())
+                .chain(rows)
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            let header = format!(
+                "{:>5}  {:<6} {:<16} {:<5} {:>5}  {:>9}",
+                "Rank", "Hand", "Type", "Wild", "Bid", "Winnings"
+            );
+            let total = hands
+                .iter()
+                .enumerate()
+                .map(|(idx, hand)| (idx + 1) * hand.bid)
+                .sum::<usize>();
+
+            std::iter::once(header)
+                .chain(rows)
+                .chain(std::iter::once(format!("\nTotal winnings: {}", total)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
     }
 }
 
 impl Day for Day7 {
     fn part1(&self, input: &str) -> String {
-        Self::calc_winnings(input, false).to_string()
+        match Self::calc_winnings(input, &Rules::normal()) {
+            Ok(winnings) => winnings.to_string(),
+            Err(err) => err,
+        }
     }
 
     fn part2(&self, input: &str) -> String {
-        Self::calc_winnings(input, true).to_string()
+        match Self::calc_winnings(input, &Rules::joker()) {
+            Ok(winnings) => winnings.to_string(),
+            Err(err) => err,
+        }
     }
 }
 
@@ -178,6 +420,16 @@ mod test {
 
     use super::*;
 
+    impl Rules {
+        /// Twos are wild but keep their place in the card order.
+        fn deuces_wild() -> Self {
+            Self {
+                wildcards: vec!['2'],
+                ..Self::normal()
+            }
+        }
+    }
+
     const INPUT: &str = r"2345A 1
     Q2KJJ 13
     Q2Q2Q 19
@@ -212,17 +464,123 @@ mod test {
         assert_eq!(day.part2(INPUT), "6839");
     }
 
+    #[test]
+    fn test_blank_lines() {
+        let day = Day7;
+
+        assert_eq!(day.part2(&format!("{}\n\n", INPUT)), "6839");
+    }
+
+    #[test]
+    fn test_report() {
+        let input = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483";
+        let report = Day7.report(input, true, true);
+        let lines = report.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "rank,hand,type,substitution,bid,winnings");
+        assert_eq!(lines[1], "1,32T3K,One pair,-,765,765");
+        assert_eq!(lines[3], "3,T55J5,Four of a kind,J=5,684,2052");
+        assert_eq!(lines[5], "5,KTJJT,Four of a kind,J=T,220,1100");
+        assert!(Day7
+            .report(input, true, false)
+            .ends_with("Total winnings: 5905"));
+    }
+
     #[rstest]
-    #[case("JJJ34", HandType::FourOfAKind, true)]
-    #[case("JJJAA", HandType::FiveOfAKind, true)]
+    #[case("JJJ34", &[4], Rules::joker())]
+    #[case("JJJAA", &[5], Rules::joker())]
+    #[case("JJJAA", &[3, 2], Rules::normal())]
+    #[case("2KKQQ", &[3, 2], Rules::deuces_wild())]
+    #[case("22KQJ", &[3], Rules::deuces_wild())]
+    #[case("22222", &[5], Rules::deuces_wild())]
     fn test_hand_type(
         #[case] cards: &str,
-        #[case] expected_hand_type: HandType,
-        #[case] joker: bool,
+        #[case] expected_pattern: &[usize],
+        #[case] rules: Rules,
     ) {
-        let cards = build_cards(cards, joker);
-        let actual_hand_type = HandType::new(cards.as_slice());
+        let cards = build_cards(cards, &rules).unwrap();
+        let actual_hand_type = rules.best_substitution(cards.as_slice()).0;
+
+        assert_eq!(
+            rules.categories[actual_hand_type.0].pattern,
+            expected_pattern
+        );
+    }
+
+    /// Every multiset of `size` cards drawn from `ranks` ranks.
+    fn card_multisets(ranks: usize, size: usize) -> Vec<Vec<Card>> {
+        if size == 0 {
+            return vec![Vec::new()];
+        }
+
+        card_multisets(ranks, size - 1)
+            .into_iter()
+            .flat_map(|hand| {
+                let lowest = hand.last().map_or(0, |card| card.0);
+                (lowest..ranks).map(move |rank| {
+                    let mut hand = hand.clone();
+                    hand.push(Card(rank));
+                    hand
+                })
+            })
+            .collect()
+    }
 
-        assert_eq!(actual_hand_type, expected_hand_type);
+    #[rstest]
+    #[case(Rules::normal())]
+    #[case(Rules::joker())]
+    #[case(Rules::deuces_wild())]
+    fn test_every_hand_matches_brute_force(#[case] rules: Rules) {
+        let hands = card_multisets(rules.card_order.len(), 5);
+        assert_eq!(hands.len(), 6188);
+
+        for cards in hands {
+            assert_eq!(
+                rules.best_substitution(&cards).0,
+                rules.classify_brute_force(&cards),
+                "{:?}",
+                cards
+            );
+        }
+    }
+
+    #[test]
+    fn test_three_card_hands() {
+        let rules = Rules {
+            hand_size: 3,
+            ..Rules::normal()
+        };
+        let input = "AKQ 1\n223 2\nKKK 3\n33A 4";
+
+        // KKK > 33A > 223 > AKQ
+        assert_eq!(
+            Day7::calc_winnings(input, &rules),
+            Ok(1 + 2 * 2 + 4 * 3 + 3 * 4)
+        );
+        assert!(Hand::new("AKQJ 1", &rules).is_err());
+        assert_eq!(
+            Day7.part1("AKQJT 1\n\nAKQJ 1"),
+            "line 3: expected 5 cards, found 4"
+        );
+    }
+
+    #[test]
+    fn test_report_labels() {
+        let rules = Rules {
+            wildcards: vec!['J', '2'],
+            ..Rules::normal()
+        };
+        let label = |hand: &str| rules.substitution(&Hand::new(hand, &rules).unwrap());
+
+        assert_eq!(label("J2KKA 1"), "J2=K");
+        assert_eq!(label("2KKA3 1"), "2=K");
+        assert_eq!(label("QKKA3 1"), "-");
+
+        let rules = Rules {
+            categories: vec![Category::new("Five of a kind", &[5])],
+            ..Rules::normal()
+        };
+        let hand = Hand::new("AKQJT 1", &rules).unwrap();
+        assert_eq!(rules.category_name(hand.hand_type), "Unranked");
     }
 }
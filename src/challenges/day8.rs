@@ -1,8 +1,12 @@
-use std::{collections::HashMap, str::FromStr};
-
-use regex::Regex;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
 
 use crate::day::Day;
+use crate::graph::{DenseWalk, Graph};
+use crate::math::{crt, lcm};
 
 #[derive(Debug, Clone, Copy)]
 enum Instruction {
@@ -22,6 +26,15 @@ impl TryFrom<char> for Instruction {
     }
 }
 
+impl From<Instruction> for char {
+    fn from(value: Instruction) -> Self {
+        match value {
+            Instruction::Left => 'L',
+            Instruction::Right => 'R',
+        }
+    }
+}
+
 impl From<Instruction> for usize {
     fn from(value: Instruction) -> Self {
         match value {
@@ -31,94 +44,541 @@ impl From<Instruction> for usize {
     }
 }
 
+/// The map, with node names interned to dense ids so walking it is just
+/// array indexing.
 #[derive(Debug)]
 struct Network {
     instructions: Vec<Instruction>,
-    nodes: HashMap<String, Vec<String>>,
+    /// Node names, indexed by id.
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+    /// Left and right successors, indexed by id.
+    successors: Vec<[u32; 2]>,
+}
+
+/// When a single ghost stands on an end node. Its walk over (node,
+/// instruction index) states must eventually repeat, so the hits split into a
+/// tail seen once and a loop repeating every `cycle_len` steps.
+#[derive(Debug)]
+struct GhostCycle {
+    /// First step of the repeating part of the walk.
+    cycle_start: usize,
+    cycle_len: usize,
+    /// End node hits before `cycle_start`.
+    tail_hits: Vec<usize>,
+    /// End node hits during the first pass of the loop.
+    cycle_hits: Vec<usize>,
+}
+
+/// What to draw on top of the plain map when exporting it to DOT.
+#[derive(Debug, Default)]
+struct DotOptions {
+    /// Fill in start (`..A`) and end (`..Z`) nodes.
+    highlight: bool,
+    /// Draw the path a ghost takes from this node to its first end node.
+    path_from: Option<String>,
+    /// Group the looping components reachable from each start node.
+    components: bool,
+}
+
+impl GhostCycle {
+    fn is_hit(&self, step: u128) -> bool {
+        if step < self.cycle_start as u128 {
+            return self.tail_hits.iter().any(|hit| *hit as u128 == step);
+        }
+
+        let offset = (step - self.cycle_start as u128) % self.cycle_len as u128;
+        self.cycle_hits
+            .iter()
+            .any(|hit| (hit - self.cycle_start) as u128 == offset)
+    }
 }
 
 impl Network {
-    fn calc_moves(&self, start_nodes: &[&str], end_match: bool) -> usize {
-        let mut cur_locations = start_nodes.to_vec();
+    /// Most end node combinations to try when ghosts hit several end nodes
+    /// per loop.
+    const MAX_COMBINATIONS: usize = 100_000;
+
+    fn id(&self, name: &str) -> Result<u32, String> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("Unknown node {}", name))
+    }
+
+    fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    fn node_names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    fn next(&self, id: u32, instruction: Instruction) -> u32 {
+        self.successors[id as usize][usize::from(instruction)]
+    }
+
+    /// A ghost's walk over (node, instruction index) states, numbered
+    /// `node * instructions + index` so they're dense ids.
+    fn walk(&self, start: u32) -> DenseWalk<impl FnMut(usize) -> usize + '_> {
+        let len = self.instructions.len();
 
-        self.instructions
+        DenseWalk::new(self.names.len() * len, start as usize * len, move |state| {
+            let (node, idx) = (state / len, state % len);
+            let next = self.next(node as u32, self.instructions[idx]);
+            next as usize * len + (idx + 1) % len
+        })
+    }
+
+    fn find_cycle(&self, start: u32, is_end: &[bool]) -> GhostCycle {
+        let len = self.instructions.len();
+        let mut walk = self.walk(start);
+        let hits = walk
+            .by_ref()
+            .enumerate()
+            .filter(|(_, state)| is_end[state / len])
+            .map(|(step, _)| step)
+            .collect::<Vec<_>>();
+        let cycle = walk
+            .found_cycle()
+            .expect("Ghost walks always end in a cycle");
+
+        let (tail_hits, cycle_hits) = hits.into_iter().partition(|hit| *hit < cycle.start);
+
+        GhostCycle {
+            cycle_start: cycle.start,
+            cycle_len: cycle.len,
+            tail_hits,
+            cycle_hits,
+        }
+    }
+
+    /// The first step, after at least one move, at which every ghost stands on
+    /// an end node at the same time. End nodes are `ZZZ`, or any node ending
+    /// in `Z` if `end_match` is set.
+    fn calc_moves(&self, start_nodes: &[&str], end_match: bool) -> Result<u128, String> {
+        if start_nodes.is_empty() {
+            return Err("No start nodes".to_string());
+        }
+
+        let is_end = self
+            .node_names()
+            .map(|node| {
+                if !end_match {
+                    node == "ZZZ"
+                } else {
+                    node.ends_with('Z')
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let cycles = start_nodes
             .iter()
-            .cycle()
-            .take_while(|node| {
-                let locs = cur_locations
-                    .iter_mut()
-                    .map(|loc| {
-                        let idx = usize::from(**node);
-                        *loc = self.nodes.get(*loc).unwrap()[idx].as_str();
-
-                        if !end_match {
-                            *loc != "ZZZ"
-                        } else {
-                            !loc.ends_with('Z')
-                        }
-                    })
+            .map(|start| Ok(self.find_cycle(self.id(start)?, &is_end)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        for (start, cycle) in start_nodes.iter().zip(&cycles) {
+            if cycle.tail_hits.is_empty() && cycle.cycle_hits.is_empty() {
+                return Err(format!(
+                    "Ghost starting at {} never reaches an end node",
+                    start
+                ));
+            }
+        }
+
+        first_meeting(&cycles)
+    }
+
+    /// The map as a plain graph, ignoring instructions, for the exports
+    /// that want more than following a single ghost.
+    fn graph(&self) -> Graph {
+        let mut graph = Graph::new(self.names.len());
+        for (id, successors) in self.successors.iter().enumerate() {
+            for next in successors {
+                graph.add_edge(id, *next as usize, ());
+            }
+        }
+
+        graph
+    }
+
+    /// Edges taken walking from `start` until it reaches an end node, after
+    /// at least one move, or starts repeating itself.
+    fn path_edges(&self, start: u32, is_end: &[bool]) -> HashSet<(u32, u32)> {
+        let len = self.instructions.len();
+        let mut edges = HashSet::new();
+
+        for (step, state) in self.walk(start).enumerate() {
+            let (node, idx) = ((state / len) as u32, state % len);
+            if step > 0 && is_end[node as usize] {
+                break;
+            }
+
+            edges.insert((node, self.next(node, self.instructions[idx])));
+        }
+
+        edges
+    }
+
+    /// Renders the map as a Graphviz digraph, with edges labelled by the
+    /// instructions that take them.
+    fn to_dot(&self, options: &DotOptions) -> Result<String, String> {
+        let is_start = |id: u32| self.name(id).ends_with('A');
+        let is_end = self
+            .node_names()
+            .map(|node| node.ends_with('Z'))
+            .collect::<Vec<_>>();
+
+        let path = match &options.path_from {
+            Some(start) => self.path_edges(self.id(start)?, &is_end),
+            None => HashSet::new(),
+        };
+
+        let mut dot = String::from("digraph network {\n    node [shape=ellipse];\n");
+
+        if options.highlight {
+            for id in 0..self.names.len() as u32 {
+                let color = if is_start(id) {
+                    "palegreen"
+                } else if is_end[id as usize] {
+                    "lightcoral"
+                } else {
+                    continue;
+                };
+
+                dot += &format!(
+                    "    {:?} [style=filled, fillcolor={}];\n",
+                    self.name(id),
+                    color
+                );
+            }
+        }
+
+        for (id, [left, right]) in self.successors.iter().enumerate() {
+            let id = id as u32;
+            let edges = if left == right {
+                vec![(*left, "LR")]
+            } else {
+                vec![(*left, "L"), (*right, "R")]
+            };
+
+            for (next, label) in edges {
+                let style = if path.contains(&(id, next)) {
+                    ", color=blue, penwidth=2"
+                } else {
+                    ""
+                };
+
+                dot += &format!(
+                    "    {:?} -> {:?} [label={:?}{}];\n",
+                    self.name(id),
+                    self.name(next),
+                    label,
+                    style
+                );
+            }
+        }
+
+        if options.components {
+            let graph = self.graph();
+            let starts = (0..self.names.len() as u32)
+                .filter(|id| is_start(*id))
+                .map(|id| (id, graph.reachable(id as usize)))
+                .collect::<Vec<_>>();
+
+            // Only components with a loop in them, single nodes without a
+            // self loop would just add noise.
+            let components =
+                graph
+                    .strongly_connected_components()
+                    .into_iter()
+                    .filter(|component| {
+                        let first = component[0];
+                        component.len() > 1 || graph.successors(first).any(|next| next == first)
+                    });
+
+            for (idx, component) in components.enumerate() {
+                let reached_from = starts
+                    .iter()
+                    .filter(|(_, reachable)| reachable[component[0]])
+                    .map(|(start, _)| self.name(*start))
                     .collect::<Vec<_>>();
+                if reached_from.is_empty() {
+                    continue;
+                }
+
+                dot += &format!(
+                    "    subgraph cluster_{} {{\n        label={:?};\n",
+                    idx,
+                    format!("reached from {}", reached_from.join(", "))
+                );
+                for member in component {
+                    dot += &format!("        {:?};\n", self.name(member as u32));
+                }
+                dot += "    }\n";
+            }
+        }
+
+        dot += "}";
+        Ok(dot)
+    }
 
-                locs.iter().any(|loc| *loc)
+    /// The instructions and each node's left and right successors as JSON.
+    fn to_json(&self) -> String {
+        let instructions = self
+            .instructions
+            .iter()
+            .map(|instruction| char::from(*instruction))
+            .collect::<String>();
+
+        let nodes = self
+            .successors
+            .iter()
+            .enumerate()
+            .map(|(id, [left, right])| {
+                format!(
+                    "    {:?}: [{:?}, {:?}]",
+                    self.name(id as u32),
+                    self.name(*left),
+                    self.name(*right)
+                )
             })
-            .count()
-            + 1
+            .collect::<Vec<_>>()
+            .join(",\n");
+
+        format!(
+            "{{\n  \"instructions\": {:?},\n  \"nodes\": {{\n{}\n  }}\n}}",
+            instructions, nodes
+        )
+    }
+}
+
+/// The first step, after at least one move, at which every ghost is on one
+/// of its hits. Expects at least one ghost, each with at least one hit.
+fn first_meeting(cycles: &[GhostCycle]) -> Result<u128, String> {
+    // Until the slowest ghost starts looping it can only finish on one of
+    // its tail hits, so check those directly.
+    let slowest = cycles.iter().max_by_key(|cycle| cycle.cycle_start).unwrap();
+    let looping_from = (slowest.cycle_start as u128).max(1);
+    if let Some(step) = slowest
+        .tail_hits
+        .iter()
+        .map(|hit| *hit as u128)
+        .filter(|hit| *hit >= 1)
+        .find(|hit| cycles.iter().all(|cycle| cycle.is_hit(*hit)))
+    {
+        return Ok(step);
+    }
+
+    // Every combined congruence ends up modulo the lcm of all the cycle
+    // lengths. Checking that fits up front means `crt` only fails below for
+    // hits that can never line up.
+    cycles
+        .iter()
+        .try_fold(1, |acc, cycle| lcm(acc, cycle.cycle_len as u128))
+        .ok_or("Combined cycle length overflows 128 bits")?;
+
+    // From there on every ghost is looping, so each end node hit is a
+    // congruence and the ghosts meet where a choice of them agrees.
+    let combinations = cycles
+        .iter()
+        .try_fold(1usize, |acc, cycle| acc.checked_mul(cycle.cycle_hits.len()))
+        .filter(|count| *count <= Network::MAX_COMBINATIONS)
+        .ok_or("Too many end node combinations to search")?;
+
+    let meetings = (0..combinations)
+        .filter_map(|mut combination| {
+            cycles.iter().try_fold((0, 1), |acc, cycle| {
+                let hit = cycle.cycle_hits[combination % cycle.cycle_hits.len()];
+                combination /= cycle.cycle_hits.len();
+
+                crt(acc, (hit as u128, cycle.cycle_len as u128))
+            })
+        })
+        .map(|(residue, modulus)| {
+            if residue >= looping_from {
+                Some(residue)
+            } else {
+                (looping_from - residue)
+                    .div_ceil(modulus)
+                    .checked_mul(modulus)?
+                    .checked_add(residue)
+            }
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Meeting step overflows 128 bits")?;
+
+    meetings
+        .into_iter()
+        .min()
+        .ok_or_else(|| "Ghosts never stand on end nodes at the same time".to_string())
+}
+
+/// Writes the network back out in the puzzle's input format.
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instructions = self
+            .instructions
+            .iter()
+            .map(|instruction| char::from(*instruction))
+            .collect::<String>();
+        writeln!(f, "{}", instructions)?;
+
+        for (id, [left, right]) in self.successors.iter().enumerate() {
+            write!(
+                f,
+                "\n{} = ({}, {})",
+                self.name(id as u32),
+                self.name(*left),
+                self.name(*right)
+            )?;
+        }
+
+        Ok(())
     }
 }
 
 impl FromStr for Network {
-    type Err = ();
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.split("\n\n").collect::<Vec<_>>();
-        let instructions = parts[0]
-            .trim()
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let (line, instruction_line) = lines.next().ok_or("missing instructions")?;
+        let instructions = instruction_line
             .chars()
-            .map(Instruction::try_from)
+            .map(|c| {
+                Instruction::try_from(c)
+                    .map_err(|_| format!("line {}: invalid instruction `{}`", line, c))
+            })
             .collect::<Result<Vec<_>, _>>()?;
 
-        let regex = Regex::new(r"([A-Z]|[1-9])\w+").unwrap();
-        let nodes = parts[1]
-            .lines()
-            .map(|line| {
-                let matches = regex.find_iter(line).collect::<Vec<_>>();
-                let node = matches[0].as_str().to_string();
-                let left = matches[1].as_str().to_string();
-                let right = matches[2].as_str().to_string();
+        let nodes = lines
+            .map(|(line, s)| {
+                parse_node(s)
+                    .map(|node| (line, node))
+                    .map_err(|err| format!("line {}: {}", line, err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut names = Vec::new();
+        let mut ids = HashMap::new();
+        for (line, [name, _, _]) in &nodes {
+            if let Some(first) = ids.get(*name) {
+                let (first_line, _) = nodes[*first as usize];
+                return Err(format!(
+                    "line {}: duplicate definition of {} (first defined on line {})",
+                    line, name, first_line
+                ));
+            }
+
+            ids.insert(name.to_string(), names.len() as u32);
+            names.push(name.to_string());
+        }
 
-                (node, vec![left, right])
+        let successors = nodes
+            .iter()
+            .map(|(line, [_, left, right])| {
+                let id = |name: &str| {
+                    ids.get(name)
+                        .copied()
+                        .ok_or_else(|| format!("line {}: unknown node {}", line, name))
+                };
+
+                Ok([id(left)?, id(right)?])
             })
-            .collect::<HashMap<_, _>>();
+            .collect::<Result<Vec<_>, String>>()?;
 
         Ok(Self {
             instructions,
-            nodes,
+            names,
+            ids,
+            successors,
         })
     }
 }
 
+/// Splits a `NAME = (LEFT, RIGHT)` line into its three node names.
+fn parse_node(s: &str) -> Result<[&str; 3], String> {
+    let (name, successors) = s.split_once('=').ok_or("expected `NAME = (LEFT, RIGHT)`")?;
+    let (left, right) = successors
+        .trim()
+        .strip_prefix('(')
+        .and_then(|successors| successors.strip_suffix(')'))
+        .and_then(|successors| successors.split_once(','))
+        .ok_or("expected `(LEFT, RIGHT)` after `=`")?;
+
+    let nodes = [name, left, right].map(str::trim);
+    for node in nodes {
+        if node.is_empty() || !node.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid node name `{}`", node));
+        }
+    }
+
+    Ok(nodes)
+}
+
 pub struct Day8;
 
+impl Day8 {
+    /// Exports the map as Graphviz DOT, or as adjacency JSON if `json` is set.
+    pub fn export(
+        &self,
+        input: &str,
+        json: bool,
+        highlight: bool,
+        path_from: Option<String>,
+        components: bool,
+    ) -> String {
+        let network = match input.parse::<Network>() {
+            Ok(network) => network,
+            Err(err) => return err,
+        };
+        if json {
+            return network.to_json();
+        }
+
+        let options = DotOptions {
+            highlight,
+            path_from,
+            components,
+        };
+        network.to_dot(&options).unwrap_or_else(|err| err)
+    }
+}
+
 impl Day for Day8 {
     fn part1(&self, input: &str) -> String {
-        let network = input.parse::<Network>().unwrap();
+        let network = match input.parse::<Network>() {
+            Ok(network) => network,
+            Err(err) => return err,
+        };
         let start_nodes = ["AAA"];
 
-        network
-            .calc_moves(start_nodes.as_slice(), false)
-            .to_string()
+        match network.calc_moves(start_nodes.as_slice(), false) {
+            Ok(moves) => moves.to_string(),
+            Err(err) => err,
+        }
     }
 
     fn part2(&self, input: &str) -> String {
-        let network = input.parse::<Network>().unwrap();
+        let network = match input.parse::<Network>() {
+            Ok(network) => network,
+            Err(err) => return err,
+        };
         let start_nodes = network
-            .nodes
-            .iter()
-            .filter(|(k, _)| k.ends_with('A'))
-            .map(|(k, _)| k.as_str())
+            .node_names()
+            .filter(|node| node.ends_with('A'))
             .collect::<Vec<_>>();
 
-        network.calc_moves(start_nodes.as_slice(), true).to_string()
+        match network.calc_moves(start_nodes.as_slice(), true) {
+            Ok(moves) => moves.to_string(),
+            Err(err) => err,
+        }
     }
 }
 
@@ -128,6 +588,12 @@ mod test {
 
     use rstest::rstest;
 
+    impl Network {
+        fn next_node(&self, node: &str, instruction: Instruction) -> Result<&str, String> {
+            Ok(self.name(self.next(self.id(node)?, instruction)))
+        }
+    }
+
     const INPUT1: &str = r"RL
 
     AAA = (BBB, CCC)
@@ -170,4 +636,189 @@ mod test {
 
         assert_eq!(day.part2(INPUT3), "6");
     }
+
+    // 11A has a one step tail and then hits an end node every other step,
+    // 22A hits 22Z every third step, 33A only hits 33Z once before getting
+    // stuck on 33C.
+    const INPUT4: &str = r"L
+
+    11A = (11Z, 11Z)
+    11Z = (11B, 11B)
+    11B = (12Z, 12Z)
+    12Z = (11C, 11C)
+    11C = (11Z, 11Z)
+    22A = (22B, 22B)
+    22B = (22Z, 22Z)
+    22Z = (22A, 22A)
+    33A = (33B, 33B)
+    33B = (33Z, 33Z)
+    33Z = (33C, 33C)
+    33C = (33C, 33C)";
+
+    /// Moves every ghost in lockstep, giving up after `limit` steps.
+    fn lockstep(network: &Network, start_nodes: &[&str], limit: usize) -> Option<u128> {
+        let mut locations = start_nodes.to_vec();
+
+        (1..=limit).find_map(|step| {
+            let instruction = network.instructions[(step - 1) % network.instructions.len()];
+            for loc in locations.iter_mut() {
+                *loc = network.next_node(loc, instruction).unwrap();
+            }
+
+            locations
+                .iter()
+                .all(|loc| loc.ends_with('Z'))
+                .then_some(step as u128)
+        })
+    }
+
+    #[rstest]
+    #[case(&["11A", "22A"], Ok(5))]
+    #[case(&["22A", "33A"], Ok(2))]
+    #[case(&["11A", "33A"], Err(()))]
+    #[case(&["11A", "22A", "33A"], Err(()))]
+    fn test_cycle_analysis(#[case] start_nodes: &[&str], #[case] expected: Result<u128, ()>) {
+        let network = INPUT4.parse::<Network>().unwrap();
+        let actual = network.calc_moves(start_nodes, true);
+
+        assert_eq!(actual.as_ref().ok(), expected.as_ref().ok());
+        assert_eq!(lockstep(&network, start_nodes, 1000), expected.ok());
+    }
+
+    #[test]
+    fn test_interning() {
+        let network = INPUT1.parse::<Network>().unwrap();
+        let aaa = network.id("AAA").unwrap();
+
+        assert_eq!(network.name(aaa), "AAA");
+        assert_eq!(network.name(network.next(aaa, Instruction::Left)), "BBB");
+        assert_eq!(network.next_node("AAA", Instruction::Right), Ok("CCC"));
+        assert!(network.id("QQQ").is_err());
+        assert_eq!(network.to_string(), INPUT1.replace("    ", ""));
+        assert!("L\n\nAAA = (BBB, AAA)".parse::<Network>().is_err());
+    }
+
+    #[test]
+    fn test_dot_export() {
+        let network = INPUT1.parse::<Network>().unwrap();
+        let options = DotOptions {
+            highlight: true,
+            path_from: Some("AAA".to_string()),
+            components: false,
+        };
+        let dot = network.to_dot(&options).unwrap();
+
+        assert!(dot.contains("\"AAA\" [style=filled, fillcolor=palegreen];"));
+        assert!(dot.contains("\"ZZZ\" [style=filled, fillcolor=lightcoral];"));
+        assert!(dot.contains("\"AAA\" -> \"CCC\" [label=\"R\", color=blue, penwidth=2];"));
+        assert!(dot.contains("\"CCC\" -> \"ZZZ\" [label=\"L\", color=blue, penwidth=2];"));
+        assert!(dot.contains("\"AAA\" -> \"BBB\" [label=\"L\"];"));
+        assert!(dot.contains("\"DDD\" -> \"DDD\" [label=\"LR\"];"));
+
+        let options = DotOptions {
+            path_from: Some("QQQ".to_string()),
+            ..Default::default()
+        };
+        assert!(network.to_dot(&options).is_err());
+    }
+
+    #[test]
+    fn test_components() {
+        let network = INPUT3.parse::<Network>().unwrap();
+        let mut components = network
+            .graph()
+            .strongly_connected_components()
+            .into_iter()
+            .map(|component| {
+                let mut names = component
+                    .into_iter()
+                    .map(|id| network.name(id as u32))
+                    .collect::<Vec<_>>();
+                names.sort();
+                names
+            })
+            .collect::<Vec<_>>();
+        components.sort();
+
+        assert_eq!(
+            components,
+            vec![
+                vec!["11A"],
+                vec!["11B", "11Z"],
+                vec!["22A"],
+                vec!["22B", "22C", "22Z"],
+                vec!["XXX"],
+            ]
+        );
+
+        let options = DotOptions {
+            components: true,
+            ..Default::default()
+        };
+        let dot = network.to_dot(&options).unwrap();
+
+        assert_eq!(dot.matches("subgraph cluster_").count(), 3);
+        assert!(dot.contains("label=\"reached from 11A, 22A\";"));
+        assert!(dot.contains("label=\"reached from 22A\";"));
+    }
+
+    #[test]
+    fn test_json_export() {
+        let network = INPUT2.parse::<Network>().unwrap();
+
+        assert_eq!(
+            network.to_json(),
+            r#"{
+  "instructions": "LLR",
+  "nodes": {
+    "AAA": ["BBB", "BBB"],
+    "BBB": ["AAA", "ZZZ"],
+    "ZZZ": ["ZZZ", "ZZZ"]
+  }
+}"#
+        );
+    }
+
+    #[rstest]
+    #[case("RL\n\n0AA = (zz_1, 0AA)\nzz_1 = (0AA, 0AA)", Ok(2))]
+    #[case("RX\n\nAAA = (AAA, AAA)", Err("line 1: invalid instruction `X`"))]
+    #[case("", Err("missing instructions"))]
+    #[case("L\n\nAAA = (BBB, AAA)", Err("line 3: unknown node BBB"))]
+    #[case(
+        "L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)",
+        Err("line 4: duplicate definition of AAA (first defined on line 3)")
+    )]
+    #[case(
+        "L\n\nAAA = AAA, AAA",
+        Err("line 3: expected `(LEFT, RIGHT)` after `=`")
+    )]
+    #[case("L\n\nAAA (AAA, AAA)", Err("line 3: expected `NAME = (LEFT, RIGHT)`"))]
+    #[case("L\n\nA-A = (AAA, AAA)", Err("line 3: invalid node name `A-A`"))]
+    #[case("L\n\nAAA = (AAA, )", Err("line 3: invalid node name ``"))]
+    fn test_parse(#[case] input: &str, #[case] expected: Result<usize, &str>) {
+        let network = input.parse::<Network>();
+
+        assert_eq!(
+            network.map(|network| network.names.len()),
+            expected.map_err(str::to_string)
+        );
+    }
+
+    #[test]
+    fn test_meeting_errors() {
+        assert_eq!(Day8.part2("L\n\nBBB = (BBB, BBB)"), "No start nodes");
+
+        // Pairwise coprime loops whose combined length doesn't fit in 128 bits.
+        let cycles = [u64::MAX, u64::MAX - 1, u64::MAX - 2].map(|len| GhostCycle {
+            cycle_start: 0,
+            cycle_len: len as usize,
+            tail_hits: Vec::new(),
+            cycle_hits: vec![1],
+        });
+        assert_eq!(
+            first_meeting(&cycles),
+            Err("Combined cycle length overflows 128 bits".to_string())
+        );
+        assert_eq!(first_meeting(&cycles[..2]), Ok(1));
+    }
 }
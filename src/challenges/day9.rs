@@ -1,79 +1,124 @@
 use std::str::FromStr;
 
 use crate::day::Day;
+use crate::sequence::{Number, Sequence};
 
-struct History {
-    readings: Vec<Vec<isize>>,
+struct History<T> {
+    /// Sequences with the line they were read from.
+    readings: Vec<(usize, Sequence<T>)>,
 }
 
-impl FromStr for History {
-    type Err = ();
+impl<T: Number> FromStr for History<T> {
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let readings = s
             .lines()
-            .map(|line| {
-                line.split_whitespace()
-                    .map(|num| num.parse::<isize>().unwrap())
-                    .collect::<Vec<_>>()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                let values = line
+                    .split_whitespace()
+                    .map(|num| {
+                        num.parse::<T>()
+                            .map_err(|_| format!("line {}: invalid reading `{}`", idx + 1, num))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok((idx + 1, Sequence::new(values)))
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, String>>()?;
 
         Ok(Self { readings })
     }
 }
 
-fn extrapolate(readings: &[isize]) -> isize {
-    if readings.iter().all(|&num| num == 0) {
-        return 0;
-    }
-
-    let diffs = readings.windows(2).fold(Vec::new(), |mut acc, window| {
-        let diff = window[1] - window[0];
-        acc.push(diff);
-        acc
-    });
+/// Sums every sequence extrapolated one step forwards, or backwards.
+fn sum_extrapolated<T: Number>(input: &str, backwards: bool) -> Result<T, String> {
+    let history = input.parse::<History<T>>()?;
+
+    history
+        .readings
+        .iter()
+        .try_fold(T::from_isize(0), |acc, (line, sequence)| {
+            let value = if backwards {
+                sequence.extrapolate_backwards(1)
+            } else {
+                sequence.extrapolate(1)
+            };
+
+            value
+                .ok()
+                .and_then(|value| acc.checked_add(&value))
+                .ok_or_else(|| format!("line {}: arithmetic overflowed", line))
+        })
+}
 
-    extrapolate(&diffs) + readings.last().unwrap()
+/// One line per sequence with its degree and both extrapolated values.
+fn report_with<T: Number>(input: &str) -> Result<String, String> {
+    let history = input.parse::<History<T>>()?;
+
+    let lines = history
+        .readings
+        .iter()
+        .map(|(line, sequence)| {
+            let overflow = |_| format!("line {}: arithmetic overflowed", line);
+            let next = sequence.extrapolate(1).map_err(overflow)?;
+            let previous = sequence.extrapolate_backwards(1).map_err(overflow)?;
+
+            let degree = match sequence.degree().map_err(overflow)? {
+                Some(degree) => format!("degree {}", degree),
+                None => format!(
+                    "never reduces to all zeros ({} readings)",
+                    sequence.values().len()
+                ),
+            };
+
+            Ok(format!(
+                "line {}: {}, next {}, previous {}",
+                line, degree, next, previous
+            ))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(lines.join("\n"))
 }
 
-fn extrapolate_backwards(readings: &[isize]) -> isize {
-    if readings.iter().all(|&num| num == 0) {
-        return 0;
-    }
+/// Extrapolates with 64 bit integers, retrying with arbitrary precision if
+/// that overflows and the `bigint` feature is enabled.
+fn solve(input: &str, backwards: bool) -> String {
+    let result = sum_extrapolated::<isize>(input, backwards).map(|sum| sum.to_string());
 
-    let diffs = readings.windows(2).fold(Vec::new(), |mut acc, window| {
-        let diff = window[1] - window[0];
-        acc.push(diff);
-        acc
+    #[cfg(feature = "bigint")]
+    let result = result.or_else(|_| {
+        sum_extrapolated::<num_bigint::BigInt>(input, backwards).map(|sum| sum.to_string())
     });
 
-    readings.first().unwrap() - extrapolate_backwards(&diffs)
+    result.unwrap_or_else(|err| err)
 }
 
 pub struct Day9;
 
+impl Day9 {
+    /// Lists each sequence's polynomial degree, flagging those that never
+    /// reduce to all zeros, along with its extrapolated values.
+    pub fn report(&self, input: &str) -> String {
+        let result = report_with::<isize>(input);
+
+        #[cfg(feature = "bigint")]
+        let result = result.or_else(|_| report_with::<num_bigint::BigInt>(input));
+
+        result.unwrap_or_else(|err| err)
+    }
+}
+
 impl Day for Day9 {
     fn part1(&self, input: &str) -> String {
-        let history = input.parse::<History>().unwrap();
-
-        history
-            .readings
-            .iter()
-            .map(|readings| extrapolate(readings))
-            .sum::<isize>()
-            .to_string()
+        solve(input, false)
     }
 
     fn part2(&self, input: &str) -> String {
-        let history = input.parse::<History>().unwrap();
-
-        history
-            .readings
-            .iter()
-            .map(|readings| extrapolate_backwards(readings))
-            .sum::<isize>()
-            .to_string()
+        solve(input, true)
     }
 }
 
@@ -94,4 +139,70 @@ mod test {
     fn test_part2() {
         assert_eq!(Day9.part2(INPUT), "2".to_string());
     }
+
+    #[test]
+    fn test_report() {
+        let input = "0 3 6 9 12 15\n1 3 6 10 15 21\n\n1 2 4 8";
+
+        assert_eq!(
+            Day9.report(input),
+            "line 1: degree 1, next 18, previous -3\n\
+             line 2: degree 2, next 28, previous 0\n\
+             line 4: never reduces to all zeros (4 readings), next 15, previous 0"
+        );
+        assert_eq!(Day9.part1("1 2 x"), "line 1: invalid reading `x`");
+    }
+
+    #[test]
+    fn test_long_sequences() {
+        // Long enough that weighting every reading separately overflows, even
+        // though the answers are small.
+        let constant = vec!["100"; 60].join(" ");
+        let linear = (0..60)
+            .map(|x| (3 * x + 7).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let input = format!("{}\n{}", constant, linear);
+
+        assert_eq!(Day9.part1(&constant), "100");
+        assert_eq!(Day9.part2(&constant), "100");
+        assert_eq!(Day9.part1(&input), (100 + 187).to_string());
+        assert_eq!(Day9.part2(&input), (100 + 4).to_string());
+        assert_eq!(
+            Day9.report(&input),
+            "line 1: degree 0, next 100, previous 100\n\
+             line 2: degree 1, next 187, previous 4"
+        );
+    }
+
+    // Alternating readings this far apart overflow 64 bits one step out.
+    const HUGE: &str = "1 -4611686018427387904 1 -4611686018427387904";
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(
+            sum_extrapolated::<isize>(HUGE, false),
+            Err("line 1: arithmetic overflowed".to_string())
+        );
+
+        #[cfg(not(feature = "bigint"))]
+        assert_eq!(Day9.part1(HUGE), "line 1: arithmetic overflowed");
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_bigint_fallback() {
+        // Check against the difference table worked in i128 instead.
+        let mut row = HUGE
+            .split_whitespace()
+            .map(|num| num.parse::<i128>().unwrap())
+            .collect::<Vec<_>>();
+        let mut next = 0;
+        while !row.is_empty() {
+            next += row.last().unwrap();
+            row = row.windows(2).map(|w| w[1] - w[0]).collect();
+        }
+
+        assert_eq!(Day9.part1(HUGE), next.to_string());
+    }
 }
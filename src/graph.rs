@@ -0,0 +1,483 @@
+//! Graph algorithms, both over an explicit adjacency list and over implicit
+//! state graphs given as a successor function.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// A directed graph over dense node ids `0..len`, with data `E` on each edge.
+/// Edges keep the order they were added in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph<E = ()> {
+    edges: Vec<Vec<(usize, E)>>,
+}
+
+impl<E> Graph<E> {
+    pub fn new(len: usize) -> Self {
+        Graph {
+            edges: (0..len).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, data: E) {
+        self.edges[from].push((to, data));
+    }
+
+    /// Outgoing edges of `node` with their data.
+    #[allow(dead_code)]
+    pub fn edges(&self, node: usize) -> &[(usize, E)] {
+        &self.edges[node]
+    }
+
+    pub fn successors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges[node].iter().map(|(to, _)| *to)
+    }
+
+    /// Fewest edges from `start` to every node, `None` for unreachable ones.
+    pub fn distances(&self, start: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        let mut queue = VecDeque::from([start]);
+        distances[start] = Some(0);
+
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node].map(|d| d + 1);
+            for next in self.successors(node) {
+                if distances[next].is_none() {
+                    distances[next] = distance;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Every node reachable from `start`, including `start` itself.
+    pub fn reachable(&self, start: usize) -> Vec<bool> {
+        self.distances(start).iter().map(Option::is_some).collect()
+    }
+
+    /// Nodes ordered so every edge points forwards, `None` if there's a cycle.
+    #[allow(dead_code)]
+    pub fn topological_sort(&self) -> Option<Vec<usize>> {
+        let mut in_degree = vec![0; self.len()];
+        for node in 0..self.len() {
+            for next in self.successors(node) {
+                in_degree[next] += 1;
+            }
+        }
+
+        let mut queue = (0..self.len())
+            .filter(|node| in_degree[*node] == 0)
+            .collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(self.len());
+
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for next in self.successors(node) {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        (order.len() == self.len()).then_some(order)
+    }
+
+    #[allow(dead_code)]
+    pub fn has_cycle(&self) -> bool {
+        self.topological_sort().is_none()
+    }
+
+    /// Strongly connected components in the reverse topological order
+    /// Tarjan's algorithm finds them.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let len = self.len();
+        let mut index = vec![None; len];
+        let mut low = vec![0; len];
+        let mut on_stack = vec![false; len];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut components = Vec::new();
+
+        for root in 0..len {
+            if index[root].is_some() {
+                continue;
+            }
+
+            // Explicit call stack of (node, next edge to visit), so deep
+            // graphs can't overflow the real one.
+            let mut calls = vec![(root, 0)];
+            index[root] = Some(next_index);
+            low[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&(node, edge)) = calls.last() {
+                if let Some(&(next, _)) = self.edges[node].get(edge) {
+                    calls.last_mut().unwrap().1 += 1;
+                    match index[next] {
+                        None => {
+                            index[next] = Some(next_index);
+                            low[next] = next_index;
+                            next_index += 1;
+                            stack.push(next);
+                            on_stack[next] = true;
+                            calls.push((next, 0));
+                        }
+                        Some(idx) if on_stack[next] => low[node] = low[node].min(idx),
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    low[parent] = low[parent].min(low[node]);
+                }
+
+                if Some(low[node]) == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+}
+
+/// Follows `came_from` links back from `end` to build the path to it.
+fn build_path<N: Clone>(states: &[N], came_from: &[Option<usize>], end: usize) -> Vec<N> {
+    let mut path = vec![states[end].clone()];
+    let mut cur = end;
+    while let Some(prev) = came_from[cur] {
+        path.push(states[prev].clone());
+        cur = prev;
+    }
+
+    path.reverse();
+    path
+}
+
+/// Shortest path by number of steps from `start` to a state matching
+/// `is_goal`, including both ends.
+#[allow(dead_code)]
+pub fn bfs<N, F, I, G>(start: N, mut successors: F, mut is_goal: G) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+    G: FnMut(&N) -> bool,
+{
+    let mut ids = HashMap::from([(start.clone(), 0)]);
+    let mut states = vec![start];
+    let mut came_from = vec![None];
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(id) = queue.pop_front() {
+        if is_goal(&states[id]) {
+            return Some(build_path(&states, &came_from, id));
+        }
+
+        for next in successors(&states[id]) {
+            if !ids.contains_key(&next) {
+                ids.insert(next.clone(), states.len());
+                queue.push_back(states.len());
+                states.push(next);
+                came_from.push(Some(id));
+            }
+        }
+    }
+
+    None
+}
+
+/// Cheapest path from `start` to a state matching `is_goal`, with its cost.
+/// `successors` gives each next state with the cost of moving to it.
+#[allow(dead_code)]
+pub fn dijkstra<N, F, I, G>(start: N, successors: F, is_goal: G) -> Option<(u64, Vec<N>)>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, u64)>,
+    G: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| 0, is_goal)
+}
+
+/// Like [`dijkstra`], guided by a `heuristic` that must never overestimate
+/// the remaining cost for the result to be the cheapest.
+#[allow(dead_code)]
+pub fn astar<N, F, I, H, G>(
+    start: N,
+    mut successors: F,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> Option<(u64, Vec<N>)>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, u64)>,
+    H: FnMut(&N) -> u64,
+    G: FnMut(&N) -> bool,
+{
+    // States are numbered as they're found so the heap doesn't need `Ord`.
+    let mut ids = HashMap::from([(start.clone(), 0)]);
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), 0))]);
+    let mut states = vec![start];
+    let mut costs = vec![0];
+    let mut came_from = vec![None];
+
+    while let Some(Reverse((estimate, id))) = heap.pop() {
+        let cost = costs[id];
+        if estimate > cost + heuristic(&states[id]) {
+            // A cheaper route here was already expanded.
+            continue;
+        }
+        if is_goal(&states[id]) {
+            return Some((cost, build_path(&states, &came_from, id)));
+        }
+
+        for (next, step) in successors(&states[id]) {
+            let next_cost = cost + step;
+            let next_id = match ids.get(&next) {
+                Some(&next_id) if costs[next_id] <= next_cost => continue,
+                Some(&next_id) => next_id,
+                None => {
+                    ids.insert(next.clone(), states.len());
+                    states.push(next);
+                    costs.push(next_cost);
+                    came_from.push(None);
+                    states.len() - 1
+                }
+            };
+
+            costs[next_id] = next_cost;
+            came_from[next_id] = Some(id);
+            heap.push(Reverse((next_cost + heuristic(&states[next_id]), next_id)));
+        }
+    }
+
+    None
+}
+
+/// Where a deterministic walk through a finite state space starts repeating:
+/// the state at step `start + len` is the one at step `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub len: usize,
+}
+
+/// The walk `start, next(start), next(next(start)), ..` over states that are
+/// dense ids in `0..len`, yielding each state until one repeats. The first
+/// step each state was seen is kept in a flat array, so no hashing is needed,
+/// and once the walk runs out [`DenseWalk::found_cycle`] says where it loops.
+pub struct DenseWalk<F> {
+    seen: Vec<Option<usize>>,
+    state: usize,
+    step: usize,
+    next: F,
+    cycle: Option<Cycle>,
+}
+
+impl<F: FnMut(usize) -> usize> DenseWalk<F> {
+    pub fn new(len: usize, start: usize, next: F) -> Self {
+        DenseWalk {
+            seen: vec![None; len],
+            state: start,
+            step: 0,
+            next,
+            cycle: None,
+        }
+    }
+
+    /// The cycle the walk ended up in, once it has yielded every state.
+    pub fn found_cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+}
+
+impl<F: FnMut(usize) -> usize> Iterator for DenseWalk<F> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.cycle.is_some() {
+            return None;
+        }
+
+        if let Some(first) = self.seen[self.state] {
+            self.cycle = Some(Cycle {
+                start: first,
+                len: self.step - first,
+            });
+            return None;
+        }
+
+        let state = self.state;
+        self.seen[state] = Some(self.step);
+        self.state = (self.next)(state);
+        self.step += 1;
+
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 0 -> 1 -> 2 -> 0 is a loop, 3 feeds into it, 4 -> 5 hangs off it and
+    /// 6 is on its own.
+    fn example() -> Graph {
+        let mut graph = Graph::new(7);
+        for (from, to) in [(0, 1), (1, 2), (2, 0), (3, 1), (2, 4), (4, 5)] {
+            graph.add_edge(from, to, ());
+        }
+
+        graph
+    }
+
+    #[test]
+    fn test_distances_and_reachable() {
+        let graph = example();
+
+        assert_eq!(
+            graph.distances(3),
+            vec![Some(3), Some(1), Some(2), Some(0), Some(3), Some(4), None]
+        );
+        assert_eq!(
+            graph.reachable(4),
+            vec![false, false, false, false, true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_components_and_topological_sort() {
+        let graph = example();
+        let mut components = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect::<Vec<_>>();
+
+        // Tarjan emits components after everything they lead to.
+        assert_eq!(components[0], vec![5]);
+        components.sort();
+        assert_eq!(
+            components,
+            vec![vec![0, 1, 2], vec![3], vec![4], vec![5], vec![6]]
+        );
+        assert!(graph.has_cycle());
+
+        let mut dag = Graph::new(5);
+        for (from, to) in [(3, 1), (1, 0), (3, 4), (4, 0), (2, 3)] {
+            dag.add_edge(from, to, ());
+        }
+        let order = dag.topological_sort().unwrap();
+        for node in 0..dag.len() {
+            let pos = |node| order.iter().position(|n| *n == node).unwrap();
+            assert!(dag.successors(node).all(|next| pos(node) < pos(next)));
+        }
+    }
+
+    const MAZE: &str = r"
+    S.#.....
+    .##.###.
+    ....#...
+    .##...#E";
+
+    fn maze_successors(grid: &[Vec<u8>], (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        [(0, 1), (2, 1), (1, 0), (1, 2)]
+            .into_iter()
+            .filter_map(|(dx, dy)| {
+                let x = (x + dx).checked_sub(1)?;
+                let y = (y + dy).checked_sub(1)?;
+                (grid.get(y)?.get(x)? != &b'#').then_some((x, y))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let grid = MAZE
+            .lines()
+            .map(|line| line.trim().bytes().collect::<Vec<_>>())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        let goal: (usize, usize) = (7, 3);
+        let successors = |pos: &(usize, usize)| maze_successors(&grid, *pos);
+        // Walking onto the bottom row costs extra.
+        let weighted = |pos: &(usize, usize)| {
+            maze_successors(&grid, *pos)
+                .into_iter()
+                .map(|next| (next, if next.1 == 3 { 5 } else { 1 }))
+        };
+        let manhattan =
+            |pos: &(usize, usize)| (goal.0.abs_diff(pos.0) + goal.1.abs_diff(pos.1)) as u64;
+
+        let path = bfs((0, 0), successors, |pos| *pos == goal).unwrap();
+        assert_eq!(path.len() - 1, 12);
+        assert!(path
+            .windows(2)
+            .all(|step| maze_successors(&grid, step[0]).contains(&step[1])));
+
+        let (cost, path) = dijkstra((0, 0), weighted, |pos| *pos == goal).unwrap();
+        // So the cheapest path takes the long way round the top.
+        assert_eq!((cost, path.len() - 1), (18, 14));
+
+        let (astar_cost, _) = astar((0, 0), weighted, manhattan, |pos| *pos == goal).unwrap();
+        assert_eq!(astar_cost, cost);
+
+        assert!(bfs((0, 0), successors, |pos| *pos == (2, 0)).is_none());
+    }
+
+    #[test]
+    fn test_dense_walk() {
+        for modulus in 1..200usize {
+            let next = |x: &usize| (x * x + 1) % modulus;
+            let mut dense = DenseWalk::new(modulus, 0, |x| next(&x));
+            let states = dense.by_ref().collect::<Vec<_>>();
+
+            // Brute force the first repeat by walking and remembering.
+            let mut walk = vec![0];
+            while !walk[..walk.len() - 1].contains(walk.last().unwrap()) {
+                walk.push(next(walk.last().unwrap()));
+            }
+            let repeat = walk.len() - 1;
+            let first = walk.iter().position(|x| *x == walk[repeat]).unwrap();
+
+            assert_eq!(states, walk[..repeat]);
+            assert_eq!(
+                dense.found_cycle(),
+                Some(Cycle {
+                    start: first,
+                    len: repeat - first
+                })
+            );
+            assert_eq!(dense.next(), None);
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Shared 2D grid toolkit for the grid based puzzles.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coord {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Coord {
+    #[allow(dead_code)]
+    pub fn new(x: usize, y: usize) -> Self {
+        Coord { x, y }
+    }
+}
+
+/// Which cells count as adjacent to a given cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    #[allow(dead_code)]
+    /// N, E, S, W
+    Four,
+    /// The four orthogonal cells plus the diagonals
+    Eight,
+}
+
+impl Neighborhood {
+    const ORTHOGONAL: [(isize, isize); 4] = [
+        (0, -1), // N
+        (-1, 0), // W
+        (1, 0),  // E
+        (0, 1),  // S
+    ];
+
+    const ALL: [(isize, isize); 8] = [
+        (-1, -1), // NW
+        (0, -1),  // N
+        (1, -1),  // NE
+        (-1, 0),  // W
+        (1, 0),   // E
+        (-1, 1),  // SW
+        (0, 1),   // S
+        (1, 1),   // SE
+    ];
+
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::Four => &Self::ORTHOGONAL,
+            Neighborhood::Eight => &Self::ALL,
+        }
+    }
+}
+
+/// A rectangular grid of cells stored in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            width * height,
+            cells.len(),
+            "Grid dimensions don't match cells"
+        );
+
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses a grid from text, converting each character with `f`. Blank
+    /// lines are skipped, every other character becomes a cell.
+    pub fn parse_with<F>(s: &str, mut f: F) -> Result<Self, &'static str>
+    where
+        F: FnMut(char) -> T,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for line in s.lines().filter(|line| !line.is_empty()) {
+            let len = line.chars().count();
+            if *width.get_or_insert(len) != len {
+                return Err("Grid rows have inconsistent widths");
+            }
+
+            cells.extend(line.chars().map(&mut f));
+            height += 1;
+        }
+
+        Ok(Grid::new(width.unwrap_or(0), height, cells))
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, coord: Coord) -> bool {
+        coord.x < self.width && coord.y < self.height
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.in_bounds(coord)
+            .then(|| &self.cells[coord.y * self.width + coord.x])
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        if self.in_bounds(coord) {
+            Some(&mut self.cells[coord.y * self.width + coord.x])
+        } else {
+            None
+        }
+    }
+
+    /// Moves `coord` by the given offset, returning `None` if it leaves the grid.
+    pub fn offset(&self, coord: Coord, dx: isize, dy: isize) -> Option<Coord> {
+        let x = coord.x.checked_add_signed(dx)?;
+        let y = coord.y.checked_add_signed(dy)?;
+        let coord = Coord { x, y };
+
+        self.in_bounds(coord).then_some(coord)
+    }
+
+    /// Neighbors of `coord` that fall inside the grid.
+    pub fn neighbors(
+        &self,
+        coord: Coord,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = Coord> + '_ {
+        neighborhood
+            .offsets()
+            .iter()
+            .filter_map(move |&(dx, dy)| self.offset(coord, dx, dy))
+    }
+
+    #[allow(dead_code)]
+    pub fn neighbors4(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.neighbors(coord, Neighborhood::Four)
+    }
+
+    pub fn neighbors8(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.neighbors(coord, Neighborhood::Eight)
+    }
+
+    /// All coordinates in row-major order.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Coord { x, y }))
+    }
+
+    /// All cells with their coordinates in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.coords().zip(self.cells.iter())
+    }
+
+    #[allow(dead_code)]
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a zero width, which an empty grid would have.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    #[allow(dead_code)]
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} out of bounds", x);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    #[allow(dead_code)]
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Every cell reachable from `start` through cells matching `pred`,
+    /// including `start` itself. Empty if `start` doesn't match.
+    #[allow(dead_code)]
+    pub fn flood_fill<P>(&self, start: Coord, neighborhood: Neighborhood, pred: P) -> Vec<Coord>
+    where
+        P: Fn(&T) -> bool,
+    {
+        let mut seen = HashSet::new();
+        self.fill_from(start, neighborhood, &pred, &mut seen)
+    }
+
+    /// Connected groups of cells matching `pred`, in row-major order of their
+    /// first cell.
+    #[allow(dead_code)]
+    pub fn regions<P>(&self, neighborhood: Neighborhood, pred: P) -> Vec<Vec<Coord>>
+    where
+        P: Fn(&T) -> bool,
+    {
+        let mut seen = HashSet::new();
+
+        self.coords()
+            .filter_map(|coord| {
+                let region = self.fill_from(coord, neighborhood, &pred, &mut seen);
+                (!region.is_empty()).then_some(region)
+            })
+            .collect()
+    }
+
+    fn fill_from<P>(
+        &self,
+        start: Coord,
+        neighborhood: Neighborhood,
+        pred: &P,
+        seen: &mut HashSet<Coord>,
+    ) -> Vec<Coord>
+    where
+        P: Fn(&T) -> bool,
+    {
+        let mut region = Vec::new();
+        if seen.contains(&start) || !self.get(start).is_some_and(pred) {
+            return region;
+        }
+
+        let mut queue = VecDeque::from([start]);
+        seen.insert(start);
+        while let Some(coord) = queue.pop_front() {
+            region.push(coord);
+
+            for neighbor in self.neighbors(coord, neighborhood) {
+                if !seen.contains(&neighbor) && pred(&self[neighbor]) {
+                    seen.insert(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        region
+    }
+}
+
+impl<T> std::ops::Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &Self::Output {
+        self.get(coord).expect("Coord out of bounds")
+    }
+}
+
+impl<T> std::ops::IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut Self::Output {
+        self.get_mut(coord).expect("Coord out of bounds")
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse_with(s, |c| c)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = "#..\n.##\n..#";
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid = INPUT.parse::<Grid<char>>().unwrap();
+
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid.to_string(), "#..\n.##\n..#");
+        assert!("##\n#".parse::<Grid<char>>().is_err());
+
+        // Spaces are cells like any other, even at the edge of a row.
+        let grid = " #\n# ".parse::<Grid<char>>().unwrap();
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.to_string(), " #\n# ");
+    }
+
+    #[test]
+    fn test_neighbors_stay_in_bounds() {
+        let grid = INPUT.parse::<Grid<char>>().unwrap();
+
+        assert_eq!(grid.neighbors8(Coord::new(0, 0)).count(), 3);
+        assert_eq!(grid.neighbors8(Coord::new(2, 2)).count(), 3);
+        assert_eq!(grid.neighbors8(Coord::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbors4(Coord::new(2, 1)).count(), 3);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = INPUT.parse::<Grid<char>>().unwrap();
+
+        assert_eq!(grid.row(1), &['.', '#', '#']);
+        assert_eq!(grid.column(2).collect::<String>(), ".##");
+        assert_eq!(grid.columns().count(), 3);
+    }
+
+    #[test]
+    fn test_regions() {
+        let grid = INPUT.parse::<Grid<char>>().unwrap();
+
+        assert_eq!(grid.regions(Neighborhood::Four, |c| *c == '#').len(), 2);
+        assert_eq!(grid.regions(Neighborhood::Eight, |c| *c == '#').len(), 1);
+        assert_eq!(
+            grid.flood_fill(Coord::new(1, 1), Neighborhood::Four, |c| *c == '#')
+                .len(),
+            3
+        );
+    }
+}
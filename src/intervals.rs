@@ -0,0 +1,550 @@
+//! Sets of `usize` ranges and piecewise offset maps over them, for the puzzles
+//! that deal in ranges too big to expand.
+
+use std::fmt;
+use std::ops::Range;
+
+/// Formats a range as a half open interval, with `usize::MAX` as infinity.
+pub fn fmt_range(range: &Range<usize>) -> String {
+    if range.end == usize::MAX {
+        format!("[{}, ∞)", range.start)
+    } else {
+        format!("[{}, {})", range.start, range.end)
+    }
+}
+
+/// A set of values kept as sorted ranges that neither overlap nor touch, so
+/// equal sets always have the same ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<Range<usize>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every value below `usize::MAX`.
+    pub fn full() -> Self {
+        Self::from(0..usize::MAX)
+    }
+
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set.
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+
+    pub fn min(&self) -> Option<usize> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, val: usize) -> bool {
+        let idx = self.ranges.partition_point(|range| range.end <= val);
+        self.ranges.get(idx).is_some_and(|range| range.start <= val)
+    }
+
+    pub fn insert(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Ranges that overlap or touch the new one get absorbed into it.
+        let first = self.ranges.partition_point(|other| other.end < range.start);
+        let last = self
+            .ranges
+            .partition_point(|other| other.start <= range.end);
+        let absorbed = &self.ranges[first..last];
+        let merged = match (absorbed.first(), absorbed.last()) {
+            (Some(head), Some(tail)) => head.start.min(range.start)..tail.end.max(range.end),
+            _ => range,
+        };
+
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    #[allow(dead_code)]
+    pub fn union(&self, other: &Self) -> Self {
+        self.ranges.iter().chain(&other.ranges).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+
+        for range in &self.ranges {
+            let mut start = range.start;
+
+            // Skip removed ranges that end before this one starts.
+            while other.ranges.get(j).is_some_and(|cut| cut.end <= start) {
+                j += 1;
+            }
+
+            let mut k = j;
+            while let Some(cut) = other.ranges.get(k).filter(|cut| cut.start < range.end) {
+                if cut.start > start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// Splits into the values below `at` and the rest.
+    #[allow(dead_code)]
+    pub fn split_at(&self, at: usize) -> (Self, Self) {
+        let below = Self::from(0..at);
+
+        (self.intersection(&below), self.difference(&below))
+    }
+
+    /// Adds `offset` to every value, `None` if any would leave `usize`.
+    #[allow(dead_code)]
+    pub fn shift(&self, offset: isize) -> Option<Self> {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|range| {
+                Some(
+                    range.start.checked_add_signed(offset)?
+                        ..range.end.checked_add_signed(offset)?,
+                )
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { ranges })
+    }
+}
+
+impl From<Range<usize>> for IntervalSet {
+    fn from(range: Range<usize>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl FromIterator<Range<usize>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<usize>>>(iter: I) -> Self {
+        let mut ranges = iter
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect::<Vec<_>>();
+        ranges.sort_unstable_by_key(|range| range.start);
+
+        let mut merged = Vec::<Range<usize>>::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        Self { ranges: merged }
+    }
+}
+
+impl fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges = self.ranges.iter().map(fmt_range).collect::<Vec<_>>();
+        write!(f, "{}", ranges.join(", "))
+    }
+}
+
+/// A piece of an [`IntervalMap`], shifting its domain by `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub domain: Range<usize>,
+    pub offset: isize,
+}
+
+impl Segment {
+    pub fn image(&self) -> Range<usize> {
+        shift(self.domain.start, self.offset)..shift(self.domain.end, self.offset)
+    }
+}
+
+fn shift(val: usize, offset: isize) -> usize {
+    val.checked_add_signed(offset)
+        .expect("Mapping shifted value out of range")
+}
+
+/// A function over `usize` made of sorted segments that each add a constant
+/// offset. The segments tile `0..usize::MAX`, values outside every piece it
+/// was built from being offset by zero, so a lookup is a binary search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalMap {
+    segments: Vec<Segment>,
+}
+
+impl IntervalMap {
+    pub fn identity() -> Self {
+        Self::from_segments(vec![Segment {
+            domain: 0..usize::MAX,
+            offset: 0,
+        }])
+    }
+
+    /// Builds a map offsetting each range by its offset. Where ranges overlap
+    /// the first one wins.
+    pub fn from_pieces<I>(pieces: I) -> Self
+    where
+        I: IntoIterator<Item = (Range<usize>, isize)>,
+    {
+        let pieces = pieces.into_iter().collect::<Vec<_>>();
+        let mut bounds = pieces
+            .iter()
+            .flat_map(|(range, _)| [range.start, range.end])
+            .chain([0, usize::MAX])
+            .collect::<Vec<_>>();
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let segments = bounds
+            .windows(2)
+            .map(|bound| {
+                let offset = pieces
+                    .iter()
+                    .find(|(range, _)| range.contains(&bound[0]))
+                    .map_or(0, |(_, offset)| *offset);
+
+                Segment {
+                    domain: bound[0]..bound[1],
+                    offset,
+                }
+            })
+            .collect();
+
+        Self::from_segments(segments)
+    }
+
+    /// Merges neighbouring segments that share an offset.
+    fn from_segments(segments: Vec<Segment>) -> Self {
+        let mut merged = Vec::<Segment>::with_capacity(segments.len());
+
+        for segment in segments.into_iter().filter(|seg| !seg.domain.is_empty()) {
+            match merged.last_mut() {
+                Some(last)
+                    if last.offset == segment.offset && last.domain.end == segment.domain.start =>
+                {
+                    last.domain.end = segment.domain.end;
+                }
+                _ => merged.push(segment),
+            }
+        }
+
+        Self { segments: merged }
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    fn segment_index(&self, val: usize) -> usize {
+        self.segments.partition_point(|seg| seg.domain.end <= val)
+    }
+
+    pub fn get(&self, val: usize) -> usize {
+        self.segments
+            .get(self.segment_index(val))
+            .map_or(val, |seg| shift(val, seg.offset))
+    }
+
+    /// Every value the map sends `set` to.
+    pub fn image(&self, set: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::new();
+
+        for range in set.ranges() {
+            let mut idx = self.segment_index(range.start);
+
+            while let Some(seg) = self.segments.get(idx) {
+                if seg.domain.start >= range.end {
+                    break;
+                }
+
+                let overlap = range.start.max(seg.domain.start)..range.end.min(seg.domain.end);
+                ranges.push(shift(overlap.start, seg.offset)..shift(overlap.end, seg.offset));
+                idx += 1;
+            }
+        }
+
+        ranges.into_iter().collect()
+    }
+
+    /// Every value the map sends into `set`.
+    pub fn preimage(&self, set: &IntervalSet) -> IntervalSet {
+        self.segments
+            .iter()
+            .flat_map(|seg| {
+                set.intersection(&seg.image().into())
+                    .ranges()
+                    .iter()
+                    .map(|range| shift(range.start, -seg.offset)..shift(range.end, -seg.offset))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// The map applying `self` and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut segments = Vec::new();
+
+        for seg in &self.segments {
+            let image = seg.image();
+            let mut idx = next.segment_index(image.start);
+
+            while let Some(next_seg) = next.segments.get(idx) {
+                if next_seg.domain.start >= image.end {
+                    break;
+                }
+
+                let overlap =
+                    image.start.max(next_seg.domain.start)..image.end.min(next_seg.domain.end);
+                segments.push(Segment {
+                    domain: shift(overlap.start, -seg.offset)..shift(overlap.end, -seg.offset),
+                    offset: seg.offset + next_seg.offset,
+                });
+                idx += 1;
+            }
+        }
+
+        Self::from_segments(segments)
+    }
+
+    /// The inverse map, if this one is a bijection.
+    #[allow(dead_code)]
+    pub fn invert(&self) -> Option<Self> {
+        let mut segments = self
+            .segments
+            .iter()
+            .map(|seg| Segment {
+                domain: seg.image(),
+                offset: -seg.offset,
+            })
+            .collect::<Vec<_>>();
+        segments.sort_by_key(|seg| seg.domain.start);
+
+        let tiles = segments
+            .windows(2)
+            .all(|pair| pair[0].domain.end == pair[1].domain.start);
+        let covers = segments.first().is_some_and(|seg| seg.domain.start == 0)
+            && segments
+                .last()
+                .is_some_and(|seg| seg.domain.end == usize::MAX);
+
+        (tiles && covers).then(|| Self::from_segments(segments))
+    }
+}
+
+impl fmt::Display for IntervalMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, seg) in self.segments.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+
+            write!(
+                f,
+                "{} -> {} ({:+})",
+                fmt_range(&seg.domain),
+                fmt_range(&seg.image()),
+                seg.offset
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util::XorShift;
+
+    const UNIVERSE: usize = 64;
+
+    /// Deterministic pseudo-random ranges inside `0..UNIVERSE`.
+    fn random_ranges(rng: &mut XorShift, count: usize) -> Vec<Range<usize>> {
+        (0..count)
+            .map(|_| {
+                let start = rng.below(UNIVERSE);
+                start..(start + rng.below(12)).min(UNIVERSE)
+            })
+            .collect()
+    }
+
+    fn members(set: &IntervalSet) -> Vec<bool> {
+        (0..UNIVERSE).map(|val| set.contains(val)).collect()
+    }
+
+    fn is_normalized(set: &IntervalSet) -> bool {
+        set.ranges().iter().all(|range| !range.is_empty())
+            && set
+                .ranges()
+                .windows(2)
+                .all(|pair| pair[0].end < pair[1].start)
+    }
+
+    #[test]
+    fn test_set_operations_match_brute_force() {
+        let mut rng = XorShift::new(0x9e3779b9);
+
+        for _ in 0..500 {
+            let a_ranges = random_ranges(&mut rng, 4);
+            let b_ranges = random_ranges(&mut rng, 4);
+            let a = a_ranges.iter().cloned().collect::<IntervalSet>();
+            let b = b_ranges.iter().cloned().collect::<IntervalSet>();
+            let in_a = (0..UNIVERSE)
+                .map(|val| a_ranges.iter().any(|range| range.contains(&val)))
+                .collect::<Vec<_>>();
+            let in_b = (0..UNIVERSE)
+                .map(|val| b_ranges.iter().any(|range| range.contains(&val)))
+                .collect::<Vec<_>>();
+
+            let mut inserted = IntervalSet::new();
+            for range in &a_ranges {
+                inserted.insert(range.clone());
+            }
+            assert_eq!(inserted, a);
+            assert_eq!(members(&a), in_a);
+            assert_eq!(a.len(), in_a.iter().filter(|x| **x).count());
+
+            let expected = |op: fn(bool, bool) -> bool| {
+                (0..UNIVERSE)
+                    .map(|val| op(in_a[val], in_b[val]))
+                    .collect::<Vec<_>>()
+            };
+            for (set, op) in [
+                (a.union(&b), (|x, y| x || y) as fn(bool, bool) -> bool),
+                (a.intersection(&b), |x, y| x && y),
+                (a.difference(&b), |x, y| x && !y),
+            ] {
+                assert!(is_normalized(&set));
+                assert_eq!(members(&set), expected(op));
+            }
+
+            let (below, above) = a.split_at(UNIVERSE / 2);
+            assert_eq!(below.union(&above), a);
+            assert!(below.ranges().iter().all(|range| range.end <= UNIVERSE / 2));
+            assert!(above.min().is_none_or(|min| min >= UNIVERSE / 2));
+        }
+    }
+
+    #[test]
+    fn test_set_shift_and_display() {
+        let set = IntervalSet::from_iter([10..20, 0..5, 4..8]);
+
+        assert_eq!(set.ranges(), &[0..8, 10..20]);
+        assert_eq!(set.shift(5).unwrap().ranges(), &[5..13, 15..25]);
+        assert!(set.shift(-1).is_none());
+        assert_eq!(set.to_string(), "[0, 8), [10, 20)");
+        assert_eq!(
+            IntervalSet::full().difference(&set).to_string(),
+            "[8, 10), [20, ∞)"
+        );
+    }
+
+    #[test]
+    fn test_map_matches_brute_force() {
+        let mut rng = XorShift::new(0x2545f491);
+
+        for _ in 0..300 {
+            let ranges = random_ranges(&mut rng, 3);
+            let pieces = ranges
+                .iter()
+                .enumerate()
+                .map(|(idx, range)| {
+                    (
+                        range.clone(),
+                        idx as isize * 7 - 3 + range.start as isize % 5,
+                    )
+                })
+                .filter(|(range, offset)| range.start as isize + offset >= 0)
+                .collect::<Vec<_>>();
+            let first_match = |val: usize| {
+                pieces
+                    .iter()
+                    .find(|(range, _)| range.contains(&val))
+                    .map_or(val, |(_, offset)| val.checked_add_signed(*offset).unwrap())
+            };
+
+            let map = IntervalMap::from_pieces(pieces.clone());
+            let twice = map.then(&map);
+            for val in 0..UNIVERSE * 2 {
+                assert_eq!(map.get(val), first_match(val));
+                assert_eq!(twice.get(val), first_match(first_match(val)));
+            }
+
+            let set = random_ranges(&mut rng, 3)
+                .into_iter()
+                .collect::<IntervalSet>();
+            let image = map.image(&set);
+            let mut expected = (0..UNIVERSE)
+                .filter(|val| set.contains(*val))
+                .map(first_match)
+                .collect::<Vec<_>>();
+            expected.sort_unstable();
+            expected.dedup();
+            assert_eq!(image.len(), expected.len());
+            assert!(expected.iter().all(|val| image.contains(*val)));
+
+            if let Some(inverse) = map.invert() {
+                for val in 0..UNIVERSE {
+                    assert_eq!(inverse.get(map.get(val)), val);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_display_and_invert() {
+        let swap = IntervalMap::from_pieces([(0..10, 10), (10..20, -10)]);
+
+        assert_eq!(swap.invert(), Some(swap.clone()));
+        assert_eq!(swap.then(&swap), IntervalMap::identity());
+        assert_eq!(
+            swap.to_string(),
+            "[0, 10) -> [10, 20) (+10)\n[10, 20) -> [0, 10) (-10)\n[20, ∞) -> [20, ∞) (+0)"
+        );
+        assert!(IntervalMap::from_pieces([(0..10, 10)]).invert().is_none());
+    }
+}
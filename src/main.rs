@@ -1,6 +1,13 @@
 mod challenges;
 mod day;
+mod graph;
+mod grid;
 mod input;
+mod intervals;
+mod math;
+mod sequence;
+#[cfg(test)]
+mod test_util;
 
 use crate::challenges::{Day1, Day2, Day3, Day4, Day5, Day6, Day7, Day8, Day9};
 use crate::day::Day;
@@ -22,6 +29,65 @@ enum Commands {
     Day { day: Option<usize> },
     #[command(arg_required_else_help = true)]
     FetchInput { day: usize },
+    /// Print the Day 3 schematic with parts, symbols and gears highlighted
+    RenderSchematic,
+    /// Print per-symbol statistics for the Day 3 schematic
+    SymbolStats {
+        /// Symbol that marks a gear
+        #[arg(long, default_value_t = '*')]
+        gear_symbol: char,
+        /// Number of parts a gear has to touch
+        #[arg(long, default_value_t = 2)]
+        gear_parts: usize,
+    },
+    /// Convert a value between two Day 5 almanac categories
+    #[command(arg_required_else_help = true)]
+    ConvertCategory {
+        value: usize,
+        source: String,
+        destination: String,
+        /// Find every source value that maps to `value` instead
+        #[arg(long)]
+        reverse: bool,
+    },
+    /// Print the Day 5 maps between two categories as a single function
+    #[command(arg_required_else_help = true)]
+    ComposeMaps { source: String, destination: String },
+    /// Report identity gaps and overlapping lines in the Day 5 almanac maps
+    ValidateAlmanac,
+    /// Trace a seed through every Day 5 map, defaulting to the lowest location
+    TraceSeed {
+        seed: Option<usize>,
+        /// Read the seeds line as ranges when picking the lowest location
+        #[arg(long)]
+        ranges: bool,
+    },
+    /// List every Day 7 hand in rank order with how it was scored
+    HandReport {
+        /// Score with jokers wild, as in part 2
+        #[arg(long)]
+        joker: bool,
+        /// Print as CSV instead of an aligned table
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Export the Day 8 map as Graphviz DOT
+    ExportNetwork {
+        /// Print plain adjacency JSON instead of DOT
+        #[arg(long)]
+        json: bool,
+        /// Highlight start (..A) and end (..Z) nodes
+        #[arg(long)]
+        highlight: bool,
+        /// Highlight the path taken from this node to its first end node
+        #[arg(long)]
+        path_from: Option<String>,
+        /// Group the strongly connected components reachable from each start
+        #[arg(long)]
+        components: bool,
+    },
+    /// Show each Day 9 sequence's polynomial degree and extrapolated values
+    SequenceReport,
 }
 
 fn main() {
@@ -41,6 +107,47 @@ fn main() {
     match args.command {
         Commands::Day { day } => day_command(day, days),
         Commands::FetchInput { day } => fetch_input_command(day, days.len()),
+        Commands::RenderSchematic => println!("{}", Day3.render(&input::get_input(3))),
+        Commands::SymbolStats {
+            gear_symbol,
+            gear_parts,
+        } => println!(
+            "{}",
+            Day3.symbol_stats(&input::get_input(3), gear_symbol, gear_parts)
+        ),
+        Commands::ConvertCategory {
+            value,
+            source,
+            destination,
+            reverse,
+        } => println!(
+            "{}",
+            Day5.convert(&input::get_input(5), value, &source, &destination, reverse)
+        ),
+        Commands::ComposeMaps {
+            source,
+            destination,
+        } => println!(
+            "{}",
+            Day5.compose(&input::get_input(5), &source, &destination)
+        ),
+        Commands::ValidateAlmanac => println!("{}", Day5.validate(&input::get_input(5))),
+        Commands::TraceSeed { seed, ranges } => {
+            println!("{}", Day5.trace(&input::get_input(5), seed, ranges))
+        }
+        Commands::HandReport { joker, csv } => {
+            println!("{}", Day7.report(&input::get_input(7), joker, csv))
+        }
+        Commands::ExportNetwork {
+            json,
+            highlight,
+            path_from,
+            components,
+        } => println!(
+            "{}",
+            Day8.export(&input::get_input(8), json, highlight, path_from, components)
+        ),
+        Commands::SequenceReport => println!("{}", Day9.report(&input::get_input(9))),
     }
 }
 
@@ -0,0 +1,315 @@
+//! Number theory helpers shared between days.
+
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// Least common multiple, `None` if it overflows. Zero if either is zero.
+pub fn lcm(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`, where `g` is
+/// never negative.
+#[allow(dead_code)]
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `a * b mod m` without overflowing, however big the operands.
+pub fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    let (mut a, mut b) = (a % m, b % m);
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+
+    // Double and add, keeping every intermediate below `m`.
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+
+    result
+}
+
+/// `a + b mod m` for `a` and `b` already below `m`.
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// `base ^ exp mod m` by repeated squaring.
+#[allow(dead_code)]
+pub fn mod_pow(base: u128, mut exp: u128, m: u128) -> u128 {
+    let mut base = base % m;
+    let mut result = 1 % m;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+
+    result
+}
+
+/// The `x` with `a * x = 1 mod m`, `None` if `a` and `m` aren't coprime.
+pub fn mod_inverse(a: u128, m: u128) -> Option<u128> {
+    if m == 0 {
+        return None;
+    }
+
+    // Extended Euclid, keeping the coefficient of `a` reduced mod `m` so it
+    // works for the whole `u128` range.
+    let (mut old_r, mut r) = (a % m, m);
+    let (mut old_x, mut x) = (1 % m, 0);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        let product = mul_mod(q, x, m);
+        let next = if old_x >= product {
+            old_x - product
+        } else {
+            old_x + (m - product)
+        };
+        (old_x, x) = (x, next);
+    }
+
+    (old_r == 1).then_some(old_x)
+}
+
+/// Combines `t = a mod m` with `t = b mod n` into a single congruence modulo
+/// `lcm(m, n)`. The moduli don't need to be coprime. `None` if the two can't
+/// both hold, a modulus is zero, or the combined modulus overflows.
+pub fn crt((a, m): (u128, u128), (b, n): (u128, u128)) -> Option<(u128, u128)> {
+    if m == 0 || n == 0 {
+        return None;
+    }
+
+    let (a, b) = (a % m, b % n);
+    let g = gcd(m, n);
+    if a % g != b % g {
+        return None;
+    }
+
+    // `a + m * k = b mod n` reduces to `(m / g) * k = (b - a) / g mod n / g`.
+    let step = n / g;
+    let diff = ((b / g) % step + step - (a / g) % step) % step;
+    let k = mul_mod(diff, mod_inverse(m / g, step)?, step);
+
+    let modulus = m.checked_mul(step)?;
+    Some((a + m * k, modulus))
+}
+
+/// The square root of `n` if it's a perfect square.
+#[allow(dead_code)]
+pub fn exact_sqrt(n: u128) -> Option<u128> {
+    let root = n.isqrt();
+
+    (root * root == n).then_some(root)
+}
+
+/// `n` choose `k`, `None` if it overflows.
+pub fn binomial(n: u128, k: u128) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+
+    let k = k.min(n - k);
+    (0..k).try_fold(1u128, |acc, i| {
+        // `acc * (n - i)` is divisible by `i + 1`, so split the divisor
+        // between the two factors instead of risking an overflow.
+        let g = gcd(acc, i + 1);
+        (acc / g).checked_mul((n - i) / ((i + 1) / g))
+    })
+}
+
+/// `x` choose `k`, generalised to negative `x` through
+/// `C(x, k) = (-1)^k * C(k - x - 1, k)`. `None` if it overflows.
+pub fn signed_binomial(x: i128, k: u128) -> Option<i128> {
+    if x >= 0 {
+        return i128::try_from(binomial(x as u128, k)?).ok();
+    }
+
+    let magnitude = binomial((x.unsigned_abs() - 1).checked_add(k)?, k)?;
+    if k.is_multiple_of(2) {
+        i128::try_from(magnitude).ok()
+    } else {
+        0i128.checked_sub_unsigned(magnitude)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    use crate::test_util::XorShift;
+
+    #[test]
+    fn test_gcd_and_lcm() {
+        for a in 0..60u128 {
+            for b in 0..60u128 {
+                let g = gcd(a, b);
+                let l = lcm(a, b).unwrap();
+
+                if g > 0 {
+                    assert_eq!((a % g, b % g), (0, 0));
+                    assert!((g + 1..=a.max(b)).all(|d| a % d != 0 || b % d != 0));
+                }
+                assert_eq!(g * l, a * b);
+            }
+        }
+
+        assert_eq!(lcm(u128::MAX, u128::MAX - 1), None);
+        assert_eq!(lcm(1 << 100, 1 << 90), Some(1 << 100));
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for a in -40..40i128 {
+            for b in -40..40i128 {
+                let (g, x, y) = extended_gcd(a, b);
+
+                assert_eq!(g as u128, gcd(a.unsigned_abs(), b.unsigned_abs()));
+                assert_eq!(a * x + b * y, g);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mod_pow_and_inverse() {
+        for m in 1..50u128 {
+            for a in 0..m {
+                let mut expected = 1 % m;
+                for exp in 0..10 {
+                    assert_eq!(mod_pow(a, exp, m), expected);
+                    expected = expected * a % m;
+                }
+
+                match mod_inverse(a, m) {
+                    Some(inverse) => assert_eq!(a * inverse % m, 1 % m),
+                    None => assert_ne!(gcd(a, m), 1),
+                }
+            }
+        }
+
+        // Fermat's little theorem for a prime near the top of the range.
+        let p = u128::MAX - 158;
+        for a in XorShift::new(7).take(20).map(u128::from) {
+            assert_eq!(mod_pow(a, p - 1, p), 1);
+            assert_eq!(mul_mod(a, mod_inverse(a, p).unwrap(), p), 1);
+        }
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((0, 1), (2, 3)), Some((2, 3)));
+        assert_eq!(crt((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(crt((1, 4), (2, 6)), None);
+        assert_eq!(crt((1, 0), (2, 6)), None);
+
+        for m in 1..25u128 {
+            for n in 1..25u128 {
+                let modulus = lcm(m, n).unwrap();
+                for a in 0..m {
+                    for b in 0..n {
+                        let expected = (0..modulus).find(|t| t % m == a && t % n == b);
+
+                        assert_eq!(crt((a, m), (b, n)), expected.map(|t| (t, modulus)));
+                    }
+                }
+            }
+        }
+
+        // Large coprime moduli whose product still fits.
+        let (m, n) = (u64::MAX as u128, (1u128 << 64) + 1);
+        let (t, modulus) = crt((5, m), (7, n)).unwrap();
+        assert_eq!((t % m, t % n, modulus), (5, 7, m * n));
+    }
+
+    #[test]
+    fn test_exact_sqrt() {
+        for n in 0..10_000u128 {
+            let root = n.isqrt();
+
+            assert_eq!(exact_sqrt(n), (root * root == n).then_some(root));
+        }
+
+        assert_eq!(exact_sqrt(u128::MAX), None);
+        assert_eq!(
+            exact_sqrt((u64::MAX as u128).pow(2)),
+            Some(u64::MAX as u128)
+        );
+    }
+
+    #[test]
+    fn test_binomial() {
+        // Pascal's triangle.
+        let mut row = vec![1u128];
+        for n in 0..130u128 {
+            for (k, expected) in row.iter().enumerate() {
+                assert_eq!(binomial(n, k as u128), Some(*expected));
+            }
+            assert_eq!(binomial(n, n + 1), Some(0));
+
+            row = std::iter::once(1)
+                .chain(row.windows(2).map(|w| w[0] + w[1]))
+                .chain(std::iter::once(1))
+                .collect();
+        }
+
+        assert_eq!(binomial(1 << 100, 1), Some(1 << 100));
+        assert_eq!(binomial(1 << 100, (1 << 100) - 1), Some(1 << 100));
+        assert_eq!(binomial(1000, 500), None);
+    }
+
+    #[rstest]
+    #[case(5, 2, Some(10))]
+    #[case(-3, 2, Some(6))]
+    #[case(-1, 7, Some(-1))]
+    #[case(-2, 3, Some(-4))]
+    #[case(4, 6, Some(0))]
+    #[case(0, 0, Some(1))]
+    #[case(-1, 0, Some(1))]
+    #[case(i128::MIN, 1, Some(i128::MIN))]
+    #[case(i128::MAX, 2, None)]
+    #[case(-(1 << 100), 2, None)]
+    fn test_signed_binomial(#[case] x: i128, #[case] k: u128, #[case] expected: Option<i128>) {
+        assert_eq!(signed_binomial(x, k), expected);
+    }
+}
@@ -0,0 +1,255 @@
+//! Sequences of readings extended as polynomials, in any integer type with
+//! checked arithmetic.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::math::signed_binomial;
+
+/// Integers a sequence can be extrapolated in. Arithmetic is checked, so
+/// fixed width types report overflow instead of wrapping.
+pub trait Number: Clone + PartialEq + FromStr + fmt::Display {
+    fn from_isize(n: isize) -> Self;
+    fn from_i128(n: i128) -> Option<Self>;
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::from_isize(0)
+    }
+}
+
+impl Number for isize {
+    fn from_isize(n: isize) -> Self {
+        n
+    }
+
+    fn from_i128(n: i128) -> Option<Self> {
+        isize::try_from(n).ok()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        isize::checked_add(*self, *rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        isize::checked_sub(*self, *rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        isize::checked_mul(*self, *rhs)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Number for num_bigint::BigInt {
+    fn from_isize(n: isize) -> Self {
+        n.into()
+    }
+
+    fn from_i128(n: i128) -> Option<Self> {
+        Some(n.into())
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Some(self - rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs)
+    }
+}
+
+/// Arithmetic on a sequence didn't fit in its number type.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Overflow;
+
+/// Readings taken at steps `0..n`, extended as the lowest degree polynomial
+/// passing through all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence<T> {
+    values: Vec<T>,
+}
+
+impl<T: Number> Sequence<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        Sequence { values }
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// The polynomial's value at step `x`, which may lie outside the
+    /// readings. This is Newton's forward difference form, summing
+    /// `C(x, j) * Δʲy₀` over the leading difference of each row. Those reach
+    /// zero past the polynomial's degree, so only the result itself can
+    /// overflow, however many readings there are.
+    pub fn value_at(&self, x: isize) -> Result<T, Overflow> {
+        let mut row = self.values.clone();
+        let mut value = T::from_isize(0);
+
+        for j in 0u128.. {
+            if row.iter().all(Number::is_zero) {
+                break;
+            }
+
+            if !row[0].is_zero() {
+                let term = signed_binomial(x as i128, j)
+                    .and_then(T::from_i128)
+                    .and_then(|weight| weight.checked_mul(&row[0]))
+                    .ok_or(Overflow)?;
+                value = value.checked_add(&term).ok_or(Overflow)?;
+            }
+
+            row = differences(&row)?;
+        }
+
+        Ok(value)
+    }
+
+    /// The value `k` steps after the last reading.
+    pub fn extrapolate(&self, k: usize) -> Result<T, Overflow> {
+        self.value_at(self.values.len() as isize - 1 + k as isize)
+    }
+
+    /// The value `k` steps before the first reading.
+    pub fn extrapolate_backwards(&self, k: usize) -> Result<T, Overflow> {
+        self.value_at(-(k as isize))
+    }
+
+    /// Degree of the polynomial the readings follow, found by differencing
+    /// until a row is all zeros. `None` if the readings run out first, in
+    /// which case the extrapolation is only a guess.
+    pub fn degree(&self) -> Result<Option<usize>, Overflow> {
+        let mut row = self.values.clone();
+
+        for degree in 0.. {
+            if row.len() < 2 {
+                break;
+            }
+
+            let diffs = differences(&row)?;
+            if diffs.iter().all(Number::is_zero) {
+                return Ok(Some(degree));
+            }
+
+            row = diffs;
+        }
+
+        Ok(None)
+    }
+}
+
+/// The differences between neighbouring values, one shorter than `row`.
+fn differences<T: Number>(row: &[T]) -> Result<Vec<T>, Overflow> {
+    row.windows(2)
+        .map(|window| window[1].checked_sub(&window[0]).ok_or(Overflow))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Extends the sequence by rebuilding the difference table, as a check on
+    /// the closed form.
+    fn extrapolate(readings: &[isize]) -> isize {
+        if readings.iter().all(|&num| num == 0) {
+            return 0;
+        }
+
+        let diffs = readings
+            .windows(2)
+            .map(|window| window[1] - window[0])
+            .collect::<Vec<_>>();
+
+        extrapolate(&diffs) + readings.last().unwrap()
+    }
+
+    fn extrapolate_backwards(readings: &[isize]) -> isize {
+        if readings.iter().all(|&num| num == 0) {
+            return 0;
+        }
+
+        let diffs = readings
+            .windows(2)
+            .map(|window| window[1] - window[0])
+            .collect::<Vec<_>>();
+
+        readings.first().unwrap() - extrapolate_backwards(&diffs)
+    }
+
+    /// `k` steps past the end, one difference table at a time.
+    fn extrapolate_steps(readings: &[isize], k: usize) -> isize {
+        let mut readings = readings.to_vec();
+        for _ in 0..k {
+            readings.push(extrapolate(&readings));
+        }
+
+        *readings.last().unwrap()
+    }
+
+    fn extrapolate_backwards_steps(readings: &[isize], k: usize) -> isize {
+        let mut readings = readings.to_vec();
+        for _ in 0..k {
+            readings.insert(0, extrapolate_backwards(&readings));
+        }
+
+        readings[0]
+    }
+
+    #[test]
+    fn test_closed_form_matches_difference_table() {
+        // Polynomials up to degree 8 with mixed sign coefficients, sampled
+        // with anything from just enough readings to 21 more than that, as
+        // long as the real inputs and with values as large.
+        let polynomials = (0..400).map(|seed: isize| {
+            (0..=seed % 9)
+                .map(|power| (seed * 7 + power * 13) % 101 - 50)
+                .collect::<Vec<_>>()
+        });
+
+        for (seed, coefficients) in polynomials.enumerate() {
+            let eval = |x: isize| {
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, coefficient| acc * x + coefficient)
+            };
+            let len = coefficients.len() + seed % 4 * 7;
+            let start = seed as isize % 9 - 4;
+            let readings = (0..len as isize)
+                .map(|x| eval(start + x))
+                .collect::<Vec<_>>();
+            let sequence = Sequence::new(readings.clone());
+
+            for (x, value) in readings.iter().enumerate() {
+                assert_eq!(sequence.value_at(x as isize), Ok(*value));
+            }
+
+            for k in 0..6 {
+                let forward = sequence.extrapolate(k).unwrap();
+                let backward = sequence.extrapolate_backwards(k).unwrap();
+
+                assert_eq!(forward, eval(start + len as isize - 1 + k as isize));
+                assert_eq!(forward, extrapolate_steps(&readings, k));
+                assert_eq!(backward, eval(start - k as isize));
+                assert_eq!(backward, extrapolate_backwards_steps(&readings, k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let sequence = Sequence::new(vec![isize::MAX - 2, isize::MAX - 1, isize::MAX]);
+
+        assert_eq!(sequence.extrapolate(1), Err(Overflow));
+        assert_eq!(sequence.degree(), Ok(Some(1)));
+    }
+}
@@ -0,0 +1,27 @@
+//! Helpers shared between the test modules.
+
+/// Small xorshift generator, so randomised tests are reproducible.
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        XorShift(seed)
+    }
+
+    /// A value in `0..max`.
+    pub fn below(&mut self, max: usize) -> usize {
+        self.next().unwrap() as usize % max
+    }
+}
+
+impl Iterator for XorShift {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        Some(self.0)
+    }
+}